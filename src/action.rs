@@ -1,6 +1,9 @@
+use std::collections::HashSet;
+
 use crate::{
-  components::home::Mode,
-  systemd::{UnitId, UnitWithStatus},
+  components::home::{BatchAction, Confirmation, LastAction, Mode},
+  keybindings::Command,
+  systemd::{Boot, Signal, UnitDependencies, UnitFile, UnitId, UnitProperties, UnitWithStatus},
 };
 
 #[derive(Debug, Clone)]
@@ -10,28 +13,103 @@ pub enum Action {
   Suspend,
   Render,
   DebouncedRender,
+  DebouncedFilter,
+  FilterUnits,
   SpinnerTick,
   Resize(u16, u16),
   ToggleShowLogger,
   RefreshServices,
   SetServices(Vec<UnitWithStatus>),
+  ToggleAgeColumn,
+  ToggleFullNames,
+  ToggleGroupInstances,
+  SetStateChangeTimestamps(Vec<(UnitId, i64)>),
+  Reinitialize,
+  CycleScope,
+  CycleUnitType,
+  ResetServices(Vec<UnitWithStatus>),
   EnterMode(Mode),
   EnterError { err: String },
+  SetStatusMessage(String),
+  ClearStatusMessage,
   CancelTask,
   ToggleHelp,
-  SetUnitFilePath { unit: UnitId, path: String },
+  RunCommand(Command),
+  SetUnitProperties { unit: UnitId, properties: Box<UnitProperties> },
   CopyUnitFilePath,
+  CopyErrorLogs,
+  CopyLogs,
+  ExportLogs,
+  CopyFilterCommand,
+  ToggleInvocationLogs,
+  ToggleCurrentBootLogs,
+  CycleLogPriority,
+  ViewBoots,
+  SetBoots(Vec<Boot>),
+  SetLogsBoot(Option<i32>),
+  ToggleChildLogs,
+  ToggleFollowLogs,
+  TogglePauseLogs,
+  ToggleLogWrap,
+  ToggleSplit,
+  ToggleLogBookmark,
+  NextLogBookmark,
+  NextLogMatch,
+  PreviousLogMatch,
+  ReloadLogs,
+  LoadMoreLogs,
+  ToggleDetailsExpanded,
+  ToggleLogsFocus,
+  ToggleRecentErrorsFilter,
+  ToggleFailedOnly,
+  ToggleInvertFilter,
+  SetUnitsWithRecentErrors(HashSet<UnitId>),
+  ViewDropIns,
+  ViewUnitDiff,
+  SetUnitDiff(Vec<String>),
+  ViewUnitCat,
+  SetUnitCat(Vec<String>),
+  EditUnitFile,
+  ViewStatus,
+  SetStatus(Vec<String>),
+  ViewDependencies,
+  SetDependencies { unit: UnitId, dependencies: UnitDependencies, units: Vec<UnitWithStatus> },
+  GoToUnit(UnitId),
+  AddUnits { units: Vec<UnitWithStatus>, go_to: UnitId },
+  OpenEnvironmentShell,
+  SaveNote,
+  RunInTerminal { program: String, args: Vec<String>, then: Option<Box<Action>> },
   SetLogs { unit: UnitId, logs: Vec<String> },
+  SetStatusLogs { unit: UnitId, logs: Vec<String> },
   AppendLogLine { unit: UnitId, line: String },
   StartService(UnitId),
   StopService(UnitId),
+  ViewKillSignals(UnitId),
+  KillService { unit: UnitId, signal: Signal },
   RestartService(UnitId),
   ReloadService(UnitId),
   EnableService(UnitId),
   DisableService(UnitId),
+  MaskService(UnitId),
+  UnmaskService(UnitId),
+  RestartWithDependencies { unit: UnitId, reverse: bool },
+  DaemonReload,
+  RestartServices(Vec<UnitId>),
+  ViewUnitFiles,
+  SetUnitFiles(Vec<UnitFile>),
+  BatchSetEnabled { units: Vec<UnitId>, enable: bool },
+  BatchServiceAction { units: Vec<UnitId>, action: BatchAction },
+  SetProcessingStatus(String),
+  SetLastAction { unit: UnitId, action: LastAction },
+  RequestConfirmation(Confirmation),
+  Confirm,
   ScrollUp(u16),
   ScrollDown(u16),
+  ScrollLeft(u16),
+  ScrollRight(u16),
   ScrollToTop,
   ScrollToBottom,
+  MouseClick { column: u16, row: u16 },
+  MouseScroll { column: u16, row: u16, down: bool },
   Noop,
 }
@@ -1,29 +1,55 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use tokio::sync::{mpsc, Mutex};
-use tracing::debug;
+use tracing::{debug, warn};
+use tui_input::Input;
 
 use crate::{
   action::Action,
-  components::{home::Home, Component},
+  components::{
+    home::{Home, Mode},
+    Component,
+  },
+  config::Config,
   event::EventHandler,
-  systemd::{get_all_services, Scope},
+  systemd::{self, get_all_services, Scope, UnitType},
   terminal::TerminalHandler,
 };
 
+// How long typing in the search box has to pause before the unit list is filtered again
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(50);
+
 pub struct App {
   pub scope: Scope,
+  pub limit_units: Vec<String>,
+  /// Seconds between unit list refreshes, 0 means never
+  pub refresh_interval: u64,
+  /// Unit to select when starting up, from `--unit`
+  pub start_unit: Option<String>,
   pub home: Arc<Mutex<Home>>,
   pub should_quit: bool,
   pub should_suspend: bool,
 }
 
 impl App {
-  pub fn new(scope: Scope) -> Result<Self> {
-    let home = Home::new(scope);
+  pub fn new(
+    scope: Scope,
+    limit_units: Vec<String>,
+    log_lines: usize,
+    refresh_interval: u64,
+    filter: Option<String>,
+    start_unit: Option<String>,
+    config: Config,
+  ) -> Result<Self> {
+    let mut home = Home::new(scope, limit_units.clone(), log_lines, config);
+    if let Some(filter) = filter {
+      // the filter is applied when the units are first set, which also selects the first match
+      home.input = Input::new(filter);
+      home.mode = Mode::ServiceList;
+    }
     let home = Arc::new(Mutex::new(home));
-    Ok(Self { scope, home, should_quit: false, should_suspend: false })
+    Ok(Self { scope, limit_units, refresh_interval, start_unit, home, should_quit: false, should_suspend: false })
   }
 
   pub async fn run(&mut self) -> Result<()> {
@@ -33,37 +59,55 @@ impl App {
 
     let cloned_action_tx = action_tx.clone();
     tokio::spawn(async move {
-      let debounce_duration = std::time::Duration::from_millis(0);
-      let debouncing = Arc::new(Mutex::new(false));
+      // the kinds of action waiting to be sent, more of the same kind in the meantime are dropped
+      let debouncing = Arc::new(Mutex::new(HashSet::new()));
 
       loop {
-        let _ = debounce_rx.recv().await;
+        let Some((action, debounce_duration)) = debounce_rx.recv().await else { break };
 
-        if *debouncing.lock().await {
+        let kind = std::mem::discriminant(&action);
+        if !debouncing.lock().await.insert(kind) {
           continue;
         }
 
-        *debouncing.lock().await = true;
-
         let action_tx = cloned_action_tx.clone();
         let debouncing = debouncing.clone();
         tokio::spawn(async move {
           tokio::time::sleep(debounce_duration).await;
-          let _ = action_tx.send(Action::Render);
-          *debouncing.lock().await = false;
+          // make room for the next one before sending, so nothing that comes in after this one is handled gets lost
+          debouncing.lock().await.remove(&kind);
+          let _ = action_tx.send(action);
         });
       }
     });
 
     self.home.lock().await.init(action_tx.clone())?;
 
-    let units = get_all_services(self.scope)
-      .await
-      .context("Unable to get services. Check that systemd is running and try running this tool with sudo.")?;
-    self.home.lock().await.set_units(units);
+    match get_all_services(self.scope, UnitType::default(), &self.limit_units).await {
+      Ok(units) => {
+        self.home.lock().await.set_units(units);
+        if let Some(unit) = &self.start_unit {
+          let action = self.home.lock().await.start_at_unit(unit);
+          let _ = action_tx.send(action);
+        }
+      },
+      Err(e) => {
+        // stay up to explain what went wrong, quitting works as usual
+        warn!("Unable to get services: {:#}", e);
+        let err = if let Some(host) = systemd::host() {
+          format!("Unable to get services from {host}.\n\n{e:#}")
+        } else if systemd::is_running() {
+          format!("Unable to get services. Try running this tool with sudo.\n\n{e:#}")
+        } else {
+          self.home.lock().await.systemd_missing = true;
+          "systemd not detected or not running. systemctl-tui only works on systems managed by systemd".into()
+        };
+        let _ = action_tx.send(Action::EnterError { err });
+      },
+    }
 
     let mut terminal = TerminalHandler::new(self.home.clone());
-    let mut event = EventHandler::new(self.home.clone(), action_tx.clone());
+    let mut event = EventHandler::new(self.home.clone(), action_tx.clone(), self.refresh_interval);
 
     terminal.render().await;
 
@@ -72,7 +116,9 @@ impl App {
         match &action {
           // these are too big to log in full
           Action::SetLogs { .. } => debug!("action: SetLogs"),
+          Action::SetStatusLogs { .. } => debug!("action: SetStatusLogs"),
           Action::SetServices { .. } => debug!("action: SetServices"),
+          Action::ResetServices { .. } => debug!("action: ResetServices"),
           _ => debug!("action: {:?}", action),
         }
 
@@ -83,12 +129,39 @@ impl App {
             let duration = start.elapsed();
             crate::utils::log_perf_event("render", duration);
           },
-          Action::DebouncedRender => debounce_tx.send(Action::Render).unwrap(),
+          Action::DebouncedRender => debounce_tx.send((Action::Render, Duration::ZERO)).unwrap(),
+          Action::DebouncedFilter => debounce_tx.send((Action::FilterUnits, SEARCH_DEBOUNCE)).unwrap(),
           Action::Noop => {},
           Action::Quit => self.should_quit = true,
           Action::Suspend => self.should_suspend = true,
           Action::Resume => self.should_suspend = false,
           Action::Resize(_, _) => terminal.render().await,
+          Action::RunInTerminal { program, args, then } => {
+            // hand the terminal over to another program (pager, editor...) until it exits
+            terminal.stop()?;
+            event.stop();
+            (&mut terminal.task).await?;
+            (&mut event.task).await?;
+            // drop the old terminal before creating a new one, otherwise its cleanup would undo the new one's setup
+            drop(terminal);
+            drop(event);
+
+            let (program, args) = systemd::terminal_cmd(program, args);
+            let status = tokio::process::Command::new(&program).args(&args).status().await;
+
+            terminal = TerminalHandler::new(self.home.clone());
+            event = EventHandler::new(self.home.clone(), action_tx.clone(), self.refresh_interval);
+            match status {
+              Ok(status) if status.success() => {
+                if let Some(then) = then {
+                  action_tx.send(*then)?;
+                }
+              },
+              Ok(status) => warn!("{} exited with {}", program, status),
+              Err(e) => action_tx.send(Action::EnterError { err: format!("Failed to run {}: {}", program, e) })?,
+            }
+            action_tx.send(Action::Render)?;
+          },
           _ => {
             if let Some(_action) = self.home.lock().await.dispatch(action) {
               action_tx.send(_action)?
@@ -102,10 +175,11 @@ impl App {
         terminal.task.await?;
         event.task.await?;
         terminal = TerminalHandler::new(self.home.clone());
-        event = EventHandler::new(self.home.clone(), action_tx.clone());
+        event = EventHandler::new(self.home.clone(), action_tx.clone(), self.refresh_interval);
         action_tx.send(Action::Resume)?;
         action_tx.send(Action::Render)?;
       } else if self.should_quit {
+        self.home.lock().await.shutdown();
         terminal.stop()?;
         event.stop();
         terminal.task.await?;
@@ -1,13 +1,16 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use duct::cmd;
-use futures::Future;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use futures::{stream, Future, StreamExt};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use indexmap::IndexMap;
 use itertools::Itertools;
 use ratatui::{
-  layout::{Constraint, Direction, Layout, Rect},
+  layout::{Alignment, Constraint, Direction, Layout, Rect},
   style::{Color, Modifier, Style},
   text::{Line, Span},
-  widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+  widgets::{
+    block::{Position, Title},
+    Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap,
+  },
 };
 use tokio::{
   io::AsyncBufReadExt,
@@ -18,12 +21,23 @@ use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 use tui_input::{backend::crossterm::EventHandler, Input};
 
-use std::{process::Stdio, time::Duration};
+use std::{
+  collections::{HashMap, HashSet, VecDeque},
+  process::Stdio,
+  time::Duration,
+};
 
 use super::{logger::Logger, Component, Frame};
 use crate::{
   action::Action,
-  systemd::{self, Scope, UnitId, UnitScope, UnitWithStatus},
+  config::{Config, PausedLogs, SearchEnter, Split},
+  keybindings::{self, Command, KeyBinding},
+  state::PersistedState,
+  systemd::{
+    self, cmd, Boot, Health, Scope, Signal, SystemdError, UnitFile, UnitId, UnitProperties, UnitScope, UnitType,
+    UnitWithStatus,
+  },
+  theme::Theme,
 };
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
@@ -35,26 +49,296 @@ pub enum Mode {
   ActionMenu,
   Processing,
   Error,
+  StateChanges,
+  DropIns,
+  EditNote,
+  Confirm,
+  UnitDiff,
+  DetailsFull,
+  Boots,
+  UnitFiles,
+  UnitCat,
+  Status,
+  LogSearch,
+  Dependencies,
+  CommandPalette,
 }
 
 #[derive(Default)]
 pub struct Home {
   pub scope: Scope,
+  pub unit_type: UnitType,
+  pub limit_units: Vec<String>,
+  pub config: Config,
   pub logger: Logger,
   pub show_logger: bool,
   pub all_units: IndexMap<UnitId, UnitWithStatus>,
   pub filtered_units: StatefulList<UnitWithStatus>,
   pub logs: Vec<String>,
   pub logs_scroll_offset: u16,
+  /// The last logs seen per unit, least recently used first. Shown while the fresh ones load
+  pub log_cache: IndexMap<UnitId, Vec<String>>,
+  /// Wrap long log lines. Otherwise they're cut off at the edge of the pane and can be scrolled sideways
+  pub log_wrap: bool,
+  pub split: Split,
+  pub logs_horizontal_offset: u16,
+  /// Rows of logs that fit in the logs pane as of the last render, for scrolling by half a page
+  pub logs_viewport_height: u16,
+  /// Columns available to log lines as of the last render, they wrap beyond that
+  pub logs_viewport_width: u16,
+  /// Where things were drawn in the last render, to find out what the mouse is pointing at
+  pub services_panel: Rect,
+  pub logs_panel: Rect,
+  pub action_menu_panel: Rect,
+  pub logs_current_invocation_only: bool,
+  pub logs_boot: Option<i32>,
+  /// Index into `LOG_PRIORITIES`, only entries at least this important are shown
+  pub logs_priority: Option<u8>,
+  /// How many lines of logs to load when a unit is selected
+  pub log_lines: usize,
+  pub logs_include_children: bool,
+  /// journalctl failed, the logs shown are the tail from `systemctl status`
+  pub logs_from_status: bool,
+  /// Only fetch a batch of logs without following new ones, to save background work while reviewing
+  pub logs_snapshot_only: bool,
+  /// Following is paused for this unit, new lines are held back (or dropped) until it's resumed
+  pub logs_paused: Option<(UnitId, VecDeque<String>)>,
+  /// Bookmarked log lines per unit, as indexes into its logs (oldest first). Moved along when the logs are refreshed
+  pub log_bookmarks: HashMap<UnitId, Vec<usize>>,
+  pub details_expanded: bool,
+  /// The details/logs pane has focus rather than the unit list. On narrow terminals only the focused pane is shown
+  pub logs_focused: bool,
+  /// Show how long each unit has been in its current state
+  pub show_age: bool,
+  /// Show units by their full name in the list, instead of leaving out `.service`
+  pub show_full_names: bool,
+  pub show_recent_errors_only: bool,
+  /// Only show failed units, like `systemctl --failed`. Applies on top of the other filters, even when inverted
+  pub show_failed_only: bool,
+  /// Show the units that *don't* match the search and other filters
+  pub invert_filter: bool,
+  pub units_with_recent_errors: Option<(std::time::Instant, HashSet<UnitId>)>,
   pub mode: Mode,
+  /// The mode to go back to when the help is closed
   pub previous_mode: Option<Mode>,
+  /// Rows the help pane is scrolled down by, it doesn't fit on smaller terminals
+  pub help_scroll: u16,
   pub input: Input,
+  pub palette_input: Input,
+  /// Everything the command palette offers, and the indexes of the ones that match what's typed
+  pub palette_items: Vec<MenuItem>,
+  pub palette_matches: StatefulList<usize>,
+  /// The search changed but the list hasn't been filtered by it yet
+  pub filter_pending: bool,
+  pub note_input: Input,
+  /// Search within the logs, separate from the unit search in `input`
+  pub log_search: Input,
+  /// What the keys do in the unit list, the defaults plus any configured bindings
+  pub keymap: HashMap<KeyBinding, Command>,
+  pub state: PersistedState,
   pub menu_items: StatefulList<MenuItem>,
+  pub drop_ins: StatefulList<String>,
+  pub confirmation: Option<Confirmation>,
+  /// The boot list, preceded by a `None` entry for "all boots"
+  pub boots: StatefulList<Option<Boot>>,
+  pub unit_files: StatefulList<UnitFile>,
+  /// Unit files marked in the unit files view for a batch enable/disable
+  pub marked_unit_files: HashSet<UnitId>,
+  /// Units marked in the unit list, the action menu acts on all of them while there are any
+  pub marked_units: HashSet<UnitId>,
+  pub last_actions: HashMap<UnitId, LastAction>,
+  /// Collapse instances of the same template (`getty@tty1.service`, `getty@tty2.service`...) into one row
+  pub group_instances: bool,
+  /// The template rows currently in the list, with how many instances each stands for
+  pub instance_groups: HashMap<UnitId, usize>,
+  pub expanded_groups: HashSet<UnitId>,
+  /// Shown next to the spinner while processing, e.g. how far along a batch action is
+  pub processing_status: Option<String>,
+  /// Lines of the vendor vs effective unit file diff, prefixed with `+`, `-` or a space
+  pub unit_diff: Vec<String>,
+  pub unit_diff_scroll: u16,
+  /// `systemctl cat` output for the selected unit
+  pub unit_cat: Vec<String>,
+  pub unit_cat_scroll: u16,
+  /// `systemctl status` output, styled from its ANSI colors
+  pub status_lines: Vec<Line<'static>>,
+  pub status_scroll: u16,
+  /// The unit whose dependencies are shown
+  pub dependencies_of: Option<UnitId>,
+  /// (property, unit name) pairs, e.g. ("Requires", "sysinit.target")
+  pub dependencies: StatefulList<(&'static str, String)>,
+  /// Active states of the units in `dependencies`, by name. Units that aren't loaded are missing
+  pub dependency_states: HashMap<String, String>,
   pub cancel_token: Option<CancellationToken>,
   pub spinner_tick: u8,
   pub error_message: String,
+  /// There's no systemd to talk to, so don't bother refreshing
+  pub systemd_missing: bool,
+  pub status_message: Option<(String, std::time::Instant)>,
+  pub state_changes: Vec<StateChange>,
+  pub health_style: HealthStyle,
+  pub theme: Theme,
   pub action_tx: Option<mpsc::UnboundedSender<Action>>,
-  pub journalctl_tx: Option<std::sync::mpsc::Sender<UnitId>>,
+  pub journalctl_tx: Option<std::sync::mpsc::Sender<LogQuery>>,
+  pub log_dispatcher_token: Option<CancellationToken>,
+}
+
+/// What the journalctl thread should fetch and follow
+#[derive(Debug, Clone)]
+pub struct LogQuery {
+  pub unit: UnitId,
+  /// Only show logs from the unit's current run, matched by `_SYSTEMD_INVOCATION_ID`
+  pub current_invocation_only: bool,
+  /// Boot offset passed to `journalctl --boot`, 0 is the current boot. `None` shows all boots
+  pub boot: Option<i32>,
+  /// Passed to `journalctl --priority`, 0 (emerg) to 7 (debug)
+  pub priority: Option<u8>,
+  /// Also match the unit's cgroup and main PID, to catch logs from child processes
+  pub include_children: bool,
+  /// Keep appending new entries after the initial batch
+  pub follow: bool,
+  /// How many lines the initial batch has
+  pub lines: usize,
+}
+
+/// What can be done to all marked units at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchAction {
+  Start,
+  Stop,
+  Restart,
+}
+
+impl BatchAction {
+  pub const ALL: [BatchAction; 3] = [BatchAction::Start, BatchAction::Stop, BatchAction::Restart];
+
+  pub fn verb(self) -> &'static str {
+    match self {
+      BatchAction::Start => "Start",
+      BatchAction::Stop => "Stop",
+      BatchAction::Restart => "Restart",
+    }
+  }
+
+  fn past(self) -> &'static str {
+    match self {
+      BatchAction::Start => "Started",
+      BatchAction::Stop => "Stopped",
+      BatchAction::Restart => "Restarted",
+    }
+  }
+
+  fn progress(self) -> &'static str {
+    match self {
+      BatchAction::Start => "Starting",
+      BatchAction::Stop => "Stopping",
+      BatchAction::Restart => "Restarting",
+    }
+  }
+}
+
+/// An action that has to be confirmed by the user before it runs
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+  pub prompt: String,
+  /// Shown as a list under the prompt, e.g. the units that will be affected
+  pub items: Vec<String>,
+  pub action: Box<Action>,
+  /// The mode to go back to if the user cancels
+  pub cancel_mode: Mode,
+}
+
+/// A change in a unit's state observed during a background refresh
+#[derive(Debug, Clone)]
+pub struct StateChange {
+  pub time: chrono::DateTime<chrono::Local>,
+  pub unit: UnitId,
+  pub from: String,
+  pub to: String,
+}
+
+/// How the last service action on a unit from this session went
+#[derive(Debug, Clone)]
+pub struct LastAction {
+  pub name: String,
+  pub time: chrono::DateTime<chrono::Local>,
+  pub succeeded: bool,
+}
+
+// Lines held back while following is paused, the oldest are dropped beyond this
+const MAX_PAUSED_LOG_LINES: usize = 10_000;
+
+// How many units' logs are kept around to show straight away when going back to them
+const LOG_CACHE_UNITS: usize = 20;
+
+// journalctl's priority levels, most important first
+const LOG_PRIORITIES: [&str; 8] = ["emerg", "alert", "crit", "err", "warning", "notice", "info", "debug"];
+
+// Marks log lines that came from journalctl's stderr rather than the journal
+const JOURNALCTL_ERROR_PREFIX: &str = "journalctl: ";
+
+// Checking every unit's logs for errors is expensive, so reuse the results for a while
+const RECENT_ERRORS_CACHE_DURATION: Duration = Duration::from_secs(5 * 60);
+
+// Rows shown in the details pane when it's collapsed
+const COMPACT_DETAILS: &[&str] = &["Description: ", "Active: "];
+
+// How many times the unit list is refreshed in the seconds after a service action, while the unit settles
+const POST_ACTION_REFRESHES: u8 = 4;
+
+// Below this width the unit list and details/logs are shown one at a time
+const NARROW_LAYOUT_WIDTH: u16 = 80;
+
+// The activity histogram in the details pane covers the last hour, one bar per bucket
+const ACTIVITY_BUCKETS: usize = 60;
+const ACTIVITY_BUCKET_SECONDS: i64 = 60;
+
+// How long a message stays in the status bar
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(4);
+
+// Only keep the most recent changes so long sessions don't grow without bound
+const MAX_STATE_CHANGES: usize = 1000;
+
+/// How a `Health` is displayed, so the glyphs and colors can be changed in one place
+pub struct HealthIndicator {
+  pub glyph: String,
+  pub color: Color,
+}
+
+pub struct HealthStyle {
+  pub healthy: HealthIndicator,
+  pub degraded: HealthIndicator,
+  pub failed: HealthIndicator,
+  pub unknown: HealthIndicator,
+}
+
+impl HealthStyle {
+  pub fn indicator(&self, health: Health) -> &HealthIndicator {
+    match health {
+      Health::Healthy => &self.healthy,
+      Health::Degraded => &self.degraded,
+      Health::Failed => &self.failed,
+      Health::Unknown => &self.unknown,
+    }
+  }
+}
+
+impl HealthStyle {
+  pub fn new(theme: &Theme, ascii: bool) -> Self {
+    let [healthy, degraded, failed, unknown] = if ascii { ["*", "~", "x", "o"] } else { ["●", "◐", "✖", "○"] };
+    Self {
+      healthy: HealthIndicator { glyph: healthy.into(), color: theme.state_active },
+      degraded: HealthIndicator { glyph: degraded.into(), color: theme.state_warning },
+      failed: HealthIndicator { glyph: failed.into(), color: theme.state_failed },
+      unknown: HealthIndicator { glyph: unknown.into(), color: theme.dim },
+    }
+  }
+}
+
+impl Default for HealthStyle {
+  fn default() -> Self {
+    Self::new(&Theme::default(), false)
+  }
 }
 
 pub struct MenuItem {
@@ -123,7 +407,7 @@ impl<T> StatefulList<T> {
     let i = match self.state.selected() {
       Some(i) => {
         if i == 0 {
-          self.items.len() - 1
+          self.items.len().saturating_sub(1)
         } else {
           i - 1
         }
@@ -143,14 +427,45 @@ impl<T> StatefulList<T> {
 }
 
 impl Home {
-  pub fn new(scope: Scope) -> Self {
-    Self { scope, ..Default::default() }
+  pub fn new(scope: Scope, limit_units: Vec<String>, log_lines: usize, config: Config) -> Self {
+    let keymap = keybindings::keymap(&config.keybindings).expect("keybindings are checked when the config is loaded");
+    let theme = config.theme;
+    let health_style = HealthStyle::new(&theme, config.ascii);
+    let logger = Logger::new(config.ascii);
+    Self {
+      scope,
+      limit_units,
+      log_lines,
+      config,
+      keymap,
+      theme,
+      health_style,
+      logger,
+      logs_boot: Some(0),
+      log_wrap: true,
+      ..Default::default()
+    }
   }
 
+  // Replace the units with a new list. Units that are in both are updated in place, keeping what was loaded for
+  // them on demand (properties, timestamps), the rest are added or dropped. Ends up in the new list's order
   pub fn set_units(&mut self, units: Vec<UnitWithStatus>) {
-    self.all_units.clear();
-    for unit_status in units.into_iter() {
-      self.all_units.insert(unit_status.id(), unit_status);
+    let order: HashMap<UnitId, usize> = units.iter().enumerate().map(|(i, u)| (u.id(), i)).collect();
+    self.all_units.retain(|id, _| order.contains_key(id));
+
+    let mut added = false;
+    for unit in units {
+      match self.all_units.get_mut(&unit.id()) {
+        Some(existing) => existing.update(unit),
+        None => {
+          self.all_units.insert(unit.id(), unit);
+          added = true;
+        },
+      }
+    }
+    // new units were appended at the end, put them in their place
+    if added {
+      self.all_units.sort_by(|a, _, b, _| order[a].cmp(&order[b]));
     }
     self.refresh_filtered_units();
   }
@@ -164,11 +479,23 @@ impl Home {
 
     for unit in units {
       if let Some(existing) = self.all_units.get_mut(&unit.id()) {
+        let changed = existing.active_state != unit.active_state || existing.sub_state != unit.sub_state;
+        if changed && !self.config.is_excluded_from_change_detection(&existing.name, existing.short_name()) {
+          self.state_changes.push(StateChange {
+            time: chrono::Local::now(),
+            unit: unit.id(),
+            from: format!("{} ({})", existing.active_state, existing.sub_state),
+            to: format!("{} ({})", unit.active_state, unit.sub_state),
+          });
+        }
         existing.update(unit);
       } else {
         self.all_units.insert(unit.id(), unit);
       }
     }
+    if self.state_changes.len() > MAX_STATE_CHANGES {
+      self.state_changes.drain(..self.state_changes.len() - MAX_STATE_CHANGES);
+    }
     info!("Updated units in {:?}", now.elapsed());
 
     let now = std::time::Instant::now();
@@ -177,28 +504,37 @@ impl Home {
   }
 
   pub fn next(&mut self) {
-    self.logs = vec![];
+    let previous = self.selected_service();
     self.filtered_units.next();
-    self.get_logs();
-    self.logs_scroll_offset = 0;
+    self.selection_changed(previous);
   }
 
   pub fn previous(&mut self) {
-    self.logs = vec![];
+    let previous = self.selected_service();
     self.filtered_units.previous();
-    self.get_logs();
-    self.logs_scroll_offset = 0;
+    self.selection_changed(previous);
   }
 
-  pub fn select(&mut self, index: Option<usize>, refresh_logs: bool) {
-    if refresh_logs {
-      self.logs = vec![];
-    }
+  pub fn select(&mut self, index: Option<usize>) {
+    let previous = self.selected_service();
     self.filtered_units.select(index);
-    if refresh_logs {
-      self.get_logs();
-      self.logs_scroll_offset = 0;
+    self.selection_changed(previous);
+  }
+
+  // Load the logs of the newly selected unit. If it's still the same unit (e.g. the list was just refreshed),
+  // its logs and scroll position are left alone
+  fn selection_changed(&mut self, previous: Option<UnitId>) {
+    if self.selected_service() == previous {
+      return;
     }
+    if let Some(previous) = previous {
+      let logs = std::mem::take(&mut self.logs);
+      self.cache_logs(previous, logs);
+    }
+    self.logs = self.selected_service().and_then(|unit| self.log_cache.get(&unit).cloned()).unwrap_or_default();
+    self.logs_scroll_offset = 0;
+    self.logs_horizontal_offset = 0;
+    self.get_logs();
   }
 
   pub fn unselect(&mut self) {
@@ -206,14 +542,226 @@ impl Home {
     self.filtered_units.unselect();
   }
 
+  fn has_recent_errors(&self, unit: &UnitWithStatus) -> bool {
+    self.units_with_recent_errors.as_ref().is_some_and(|(_, units)| units.contains(&unit.id()))
+  }
+
+  // Check every unit's logs for recent errors in the background, then turn on the recent errors filter
+  fn find_units_with_recent_errors(&mut self) {
+    let tx = self.action_tx.clone().unwrap();
+    let cancel_token = CancellationToken::new();
+    self.cancel_token = Some(cancel_token.clone());
+    let units = self.all_units.keys().cloned().collect_vec();
+
+    let spinner_task = spawn_spinner(tx.clone());
+    tokio::spawn(async move {
+      tx.send(Action::EnterMode(Mode::Processing)).unwrap();
+      let start = std::time::Instant::now();
+      let checks = stream::iter(units).map(logged_recent_errors).buffer_unordered(16).collect::<Vec<_>>();
+
+      tokio::select! {
+        _ = cancel_token.cancelled() => warn!("Checking units for recent errors was cancelled"),
+        results = checks => {
+          info!("Checked units for recent errors in {:?}", start.elapsed());
+          let units = results.into_iter().filter_map(|(unit, has_errors)| has_errors.then_some(unit)).collect();
+          tx.send(Action::SetUnitsWithRecentErrors(units)).unwrap();
+        }
+      }
+      spinner_task.abort();
+    });
+  }
+
+  fn services_title(&self) -> String {
+    let mut qualifiers = vec![];
+    if self.invert_filter {
+      qualifiers.push("NOT matching filter");
+    }
+    if self.show_recent_errors_only {
+      qualifiers.push("recent errors");
+    }
+    if self.show_failed_only {
+      qualifiers.push("failed only");
+    }
+    match self.unit_type {
+      UnitType::Service => {},
+      UnitType::All => qualifiers.push("all types"),
+      t => qualifiers.push(t.as_str()),
+    }
+    match self.scope {
+      Scope::Global => qualifiers.push("system"),
+      Scope::User => qualifiers.push("user"),
+      Scope::All => {},
+    }
+
+    if qualifiers.is_empty() {
+      " Services ".into()
+    } else {
+      format!(" Services ({}) ", qualifiers.join(", "))
+    }
+  }
+
+  fn logs_title(&self) -> String {
+    let mut qualifiers = vec![];
+    if self.logs_from_status {
+      qualifiers.push("from systemctl status, journalctl unavailable");
+    }
+    if self.logs_current_invocation_only {
+      qualifiers.push("current invocation");
+    }
+    let boot;
+    match self.logs_boot {
+      Some(0) => qualifiers.push("this boot"),
+      Some(offset) => {
+        boot = format!("boot {offset}, not following");
+        qualifiers.push(&boot);
+      },
+      None => {},
+    }
+    let priority;
+    if let Some(level) = self.logs_priority {
+      priority = format!("priority {}", LOG_PRIORITIES[level as usize]);
+      qualifiers.push(&priority);
+    }
+    if self.logs_include_children {
+      qualifiers.push("with children");
+    }
+    if self.logs_snapshot_only {
+      qualifiers.push("snapshot, not following");
+    }
+    if !self.log_wrap {
+      qualifiers.push("not wrapped");
+    }
+    let paused;
+    if let Some((unit, buffered)) = &self.logs_paused {
+      if self.selected_service().as_ref() == Some(unit) {
+        paused = match buffered.len() {
+          0 => "paused".to_string(),
+          n => format!("paused, {n} new"),
+        };
+        qualifiers.push(&paused);
+      }
+    }
+
+    if qualifiers.is_empty() {
+      " Service Logs ".into()
+    } else {
+      format!(" Service Logs ({}) ", qualifiers.join(", "))
+    }
+  }
+
+  // Scroll the logs to the next (further down, i.e. older) or previous line matching the log search, wrapping around.
+  // Returns false if nothing matches
+  fn jump_to_log_match(&mut self, forward: bool, include_current: bool) -> bool {
+    let query = self.log_search.value().to_ascii_lowercase();
+    if query.is_empty() {
+      return false;
+    }
+    // scroll offsets of the matching lines
+    let line_offsets = self.log_line_offsets();
+    let offsets = self
+      .logs
+      .iter()
+      .zip(line_offsets)
+      .filter(|(l, _)| l.to_ascii_lowercase().contains(&query))
+      .map(|(_, offset)| offset)
+      .sorted()
+      .collect_vec();
+    let (Some(&first), Some(&last)) = (offsets.first(), offsets.last()) else { return false };
+
+    let current = self.logs_scroll_offset;
+    self.logs_scroll_offset = match (forward, include_current) {
+      (true, true) => offsets.iter().copied().find(|&o| o >= current).unwrap_or(first),
+      (true, false) => offsets.iter().copied().find(|&o| o > current).unwrap_or(first),
+      (false, _) => offsets.iter().copied().rev().find(|&o| o < current).unwrap_or(last),
+    };
+    true
+  }
+
+  // Rows a log line takes up in the logs pane. Lines wrap to the width of the pane;
+  // word wrapping can occasionally take a row more than this, close enough
+  fn log_line_rows(&self, line: &str) -> usize {
+    match self.log_wrap {
+      true => Span::raw(line).width().max(1).div_ceil(self.logs_viewport_width.max(1) as usize),
+      false => 1,
+    }
+  }
+
+  /// For each log line, the scroll offset (in rows) that puts it at the top of the logs pane.
+  /// The logs are shown newest first, so that's the rows taken by the lines after it
+  fn log_line_offsets(&self) -> Vec<u16> {
+    let mut offsets = vec![0; self.logs.len()];
+    let mut rows = 0;
+    for (index, line) in self.logs.iter().enumerate().rev() {
+      offsets[index] = rows.min(u16::MAX as usize) as u16;
+      rows += self.log_line_rows(line);
+    }
+    offsets
+  }
+
+  /// The index of the log line at the top of the logs pane
+  fn log_line_at_scroll_offset(&self) -> Option<usize> {
+    let mut rows = 0;
+    for (index, line) in self.logs.iter().enumerate().rev() {
+      rows += self.log_line_rows(line);
+      if rows > self.logs_scroll_offset as usize {
+        return Some(index);
+      }
+    }
+    None
+  }
+
   pub fn selected_service(&self) -> Option<UnitId> {
     self.filtered_units.selected().map(|u| u.id())
   }
 
+  fn cache_logs(&mut self, unit: UnitId, logs: Vec<String>) {
+    // the tail from systemctl status is only a stand-in
+    if logs.is_empty() || self.logs_from_status {
+      return;
+    }
+    self.log_cache.shift_remove(&unit);
+    self.log_cache.insert(unit, logs);
+    if self.log_cache.len() > LOG_CACHE_UNITS {
+      self.log_cache.shift_remove_index(0);
+    }
+  }
+
+  // Clear the logs and fetch them again from scratch, e.g. after the log query changed
+  pub fn reload_logs(&mut self) {
+    // cached logs are from the old query
+    self.log_cache.clear();
+    self.logs = vec![];
+    self.logs_scroll_offset = 0;
+    self.get_logs();
+  }
+
+  fn log_query(&self, unit: UnitId) -> LogQuery {
+    LogQuery {
+      unit,
+      current_invocation_only: self.logs_current_invocation_only,
+      boot: self.logs_boot,
+      priority: self.logs_priority,
+      include_children: self.logs_include_children,
+      // a past boot's journal doesn't get any new entries
+      follow: !self.logs_snapshot_only && matches!(self.logs_boot, None | Some(0)),
+      lines: self.log_lines,
+    }
+  }
+
   pub fn get_logs(&mut self) {
+    // a template row has no logs of its own, its instances do
+    if self.selected_group().is_some() {
+      self.logs = vec![];
+      return;
+    }
     if let Some(selected) = self.filtered_units.selected() {
-      let unit_id = selected.id();
-      if let Err(e) = self.journalctl_tx.as_ref().unwrap().send(unit_id) {
+      // not set up before init, or gone after shutdown
+      let Some(journalctl_tx) = &self.journalctl_tx else {
+        warn!("No journalctl thread to get logs for {}", selected.name);
+        return;
+      };
+      let query = self.log_query(selected.id());
+      if let Err(e) = journalctl_tx.send(query) {
         warn!("Error sending unit name to journalctl thread: {}", e);
       }
     } else {
@@ -221,135 +769,657 @@ impl Home {
     }
   }
 
+  /// A `systemctl list-units` command that lists (roughly) the units currently shown, for use in scripts
+  fn filter_as_systemctl_command(&self) -> String {
+    let search = self.input.value();
+    // systemctl globs can't express fuzzy search and the other filters; fall back to listing the shown units by name
+    let patterns = if self.show_recent_errors_only || self.invert_filter || !search.is_empty() {
+      self.filtered_units.items.iter().map(|u| u.name.clone()).unique().collect_vec()
+    } else if !self.limit_units.is_empty() {
+      self.limit_units.clone()
+    } else {
+      vec![self.unit_type.pattern()]
+    };
+    let mut patterns = patterns.iter().map(|p| format!("'{p}'")).join(" ");
+    if self.show_failed_only {
+      patterns.insert_str(0, "--state=failed ");
+    }
+
+    match self.scope {
+      Scope::Global => format!("systemctl list-units --all {patterns}"),
+      Scope::User => format!("systemctl --user list-units --all {patterns}"),
+      Scope::All => format!("systemctl list-units --all {patterns}; systemctl --user list-units --all {patterns}"),
+    }
+  }
+
+  // Fetch the state change timestamps for the age column, for units that don't have one yet
+  fn fetch_state_change_timestamps(&self) {
+    let tx = self.action_tx.clone().unwrap();
+    let (global, user): (Vec<_>, Vec<_>) = self
+      .all_units
+      .values()
+      .filter(|u| u.state_change_timestamp.is_none())
+      .partition(|u| u.scope == UnitScope::Global);
+    let global = global.into_iter().map(|u| u.name.clone()).collect_vec();
+    let user = user.into_iter().map(|u| u.name.clone()).collect_vec();
+
+    tokio::task::spawn_blocking(move || {
+      let mut timestamps = vec![];
+      for (scope, names) in [(UnitScope::Global, global), (UnitScope::User, user)] {
+        match systemd::get_state_change_timestamps(scope, &names) {
+          Ok(t) => timestamps.extend(t.into_iter().map(|(name, timestamp)| (UnitId { name, scope }, timestamp))),
+          Err(e) => error!("Failed to get state change timestamps for {:?} units: {}", scope, e),
+        }
+      }
+      let _ = tx.send(Action::SetStateChangeTimestamps(timestamps));
+    });
+  }
+
   fn refresh_filtered_units(&mut self) {
     let previously_selected = self.selected_service();
-    let search_value_lower = self.input.value().to_lowercase();
-    // TODO: use fuzzy find
-    let matching = self
+    let search_value = self.input.value();
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut matching = self
       .all_units
       .values()
-      .filter(|u| u.short_name().to_lowercase().contains(&search_value_lower))
-      .cloned()
+      .filter_map(|u| {
+        // an empty search matches everything with the same score, so the original order is kept
+        let score =
+          if search_value.is_empty() { Some(0) } else { matcher.fuzzy_match(self.list_name(u), search_value) };
+        let matches = score.is_some() && (!self.show_recent_errors_only || self.has_recent_errors(u));
+        let failed_ok = !self.show_failed_only || u.is_failed() || u.sub_state == "failed";
+        (matches != self.invert_filter && failed_ok).then(|| (score.unwrap_or(0), u))
+      })
       .collect_vec();
-    self.filtered_units.items = matching;
+    // best match first, the sort is stable so equally good matches keep their order
+    matching.sort_by(|(a, _), (b, _)| b.cmp(a));
+    let units = matching.into_iter().map(|(_, u)| u.clone()).collect_vec();
+    self.filtered_units.items = if self.group_instances { self.group_by_template(units) } else { units };
 
-    // try to select the same item we had selected before
-    // TODO: this is horrible, clean it up
-    if let Some(previously_selected) = previously_selected {
-      if let Some(index) = self
-        .filtered_units
-        .items
+    // try to select the same item we had selected before, if we can't, select the first item in the list.
+    // The old index may point at a different unit in the new list, so compare against the unit we had.
+    // An instance that was collapsed since is found through its template's row
+    let position =
+      |id: &UnitId| self.filtered_units.items.iter().position(|u| u.name == id.name && u.scope == id.scope);
+    let index = previously_selected
+      .as_ref()
+      .and_then(|p| {
+        position(p).or_else(|| {
+          let template = self.all_units.get(p)?.template()?;
+          position(&UnitId { name: template, scope: p.scope })
+        })
+      })
+      .or_else(|| (!self.filtered_units.items.is_empty()).then_some(0));
+    self.filtered_units.select(index);
+    self.selection_changed(previously_selected);
+  }
+
+  // Replace instances of the same template with a row for the template where the first of them was, followed by the
+  // instances if it's expanded. Templates with a single instance are left alone
+  fn group_by_template(&mut self, units: Vec<UnitWithStatus>) -> Vec<UnitWithStatus> {
+    let mut groups: IndexMap<UnitId, Vec<UnitWithStatus>> = IndexMap::new();
+    for unit in &units {
+      if let Some(template) = unit.template() {
+        groups.entry(UnitId { name: template, scope: unit.scope }).or_default().push(unit.clone());
+      }
+    }
+    groups.retain(|_, instances| instances.len() > 1);
+    self.instance_groups = groups.iter().map(|(id, instances)| (id.clone(), instances.len())).collect();
+
+    let mut grouped = vec![];
+    for unit in units {
+      let Some(template) = unit.template().map(|name| UnitId { name, scope: unit.scope }) else {
+        grouped.push(unit);
+        continue;
+      };
+      let Some(instances) = groups.shift_remove(&template) else {
+        // ungrouped, or already added with its group
+        if !self.instance_groups.contains_key(&template) {
+          grouped.push(unit);
+        }
+        continue;
+      };
+      // the worst state of the instances stands for the group
+      let worst = instances
         .iter()
-        .position(|u| u.name == previously_selected.name && u.scope == previously_selected.scope)
-      {
-        self.select(Some(index), false);
-      } else {
-        self.select(Some(0), true);
+        .find(|u| u.is_failed())
+        .or_else(|| instances.iter().find(|u| u.is_active()))
+        .unwrap_or(&instances[0]);
+      grouped.push(UnitWithStatus {
+        name: template.name.clone(),
+        scope: template.scope,
+        description: format!("{} instances", instances.len()),
+        properties: None,
+        state_change_timestamp: None,
+        load_state: worst.load_state.clone(),
+        active_state: worst.active_state.clone(),
+        sub_state: worst.sub_state.clone(),
+      });
+      if self.expanded_groups.contains(&template) {
+        grouped.extend(instances);
       }
+    }
+    grouped
+  }
+
+  // The selected row's template group, if it's a template row
+  fn selected_group(&self) -> Option<UnitId> {
+    self.selected_service().filter(|id| self.instance_groups.contains_key(id))
+  }
+
+  fn set_group_expanded(&mut self, group: UnitId, expanded: bool) {
+    if expanded {
+      self.expanded_groups.insert(group);
     } else {
-      // if we can't, select the first item in the list
-      if !self.filtered_units.items.is_empty() {
-        self.select(Some(0), true);
-      } else {
-        self.unselect();
+      self.expanded_groups.remove(&group);
+    }
+    self.refresh_filtered_units();
+  }
+
+  // What goes before a name while grouping instances: whether a template row is expanded, or indentation for the
+  // instances below one
+  fn group_prefix(&self, unit: &UnitWithStatus) -> &'static str {
+    if !self.group_instances {
+      return "";
+    }
+    let id = unit.id();
+    if self.instance_groups.contains_key(&id) {
+      match (self.expanded_groups.contains(&id), self.config.ascii) {
+        (true, false) => "▾ ",
+        (false, false) => "▸ ",
+        (true, true) => "v ",
+        (false, true) => "> ",
       }
+    } else if unit.template().is_some_and(|name| self.instance_groups.contains_key(&UnitId { name, scope: unit.scope }))
+    {
+      "  "
+    } else {
+      ""
+    }
+  }
+
+  // The name a unit goes by in the list and the details, which the search matches against too
+  fn list_name<'a>(&self, unit: &'a UnitWithStatus) -> &'a str {
+    if self.show_full_names {
+      &unit.name
+    } else {
+      unit.short_name()
+    }
+  }
+
+  // Select the unit (and load its logs) if it's in the filtered list
+  fn select_unit(&mut self, unit: &UnitId) -> bool {
+    let Some(index) = self.filtered_units.items.iter().position(|u| u.name == unit.name && u.scope == unit.scope)
+    else {
+      return false;
+    };
+    self.select(Some(index));
+    true
+  }
+
+  /// Select a unit by name when starting up and load its logs, or explain that it isn't there
+  pub fn start_at_unit(&mut self, name: &str) -> Action {
+    // prefer the system unit when a user unit has the same name
+    match self.all_units.keys().filter(|id| id.name == name).min_by_key(|id| id.scope == UnitScope::User) {
+      Some(unit) => Action::GoToUnit(unit.clone()),
+      None => Action::EnterError { err: format!("Unit {name} wasn't found, check the name and --scope") },
     }
   }
 
   fn start_service(&mut self, service: UnitId) {
     let cancel_token = CancellationToken::new();
     let future = systemd::start_service(service.clone(), cancel_token.clone());
-    self.service_action(service, "Start".into(), cancel_token, future);
+    self.service_action(service, "Start".into(), "start".into(), cancel_token, future);
   }
 
   fn stop_service(&mut self, service: UnitId) {
     let cancel_token = CancellationToken::new();
     let future = systemd::stop_service(service.clone(), cancel_token.clone());
-    self.service_action(service, "Stop".into(), cancel_token, future);
+    self.service_action(service, "Stop".into(), "stop".into(), cancel_token, future);
+  }
+
+  fn mask_service(&mut self, service: UnitId) {
+    let cancel_token = CancellationToken::new();
+    let future = systemd::mask_service(service.clone(), cancel_token.clone());
+    self.service_action(service, "Mask".into(), "mask".into(), cancel_token, future);
+  }
+
+  fn unmask_service(&mut self, service: UnitId) {
+    let cancel_token = CancellationToken::new();
+    let future = systemd::unmask_service(service.clone(), cancel_token.clone());
+    self.service_action(service, "Unmask".into(), "unmask".into(), cancel_token, future);
+  }
+
+  fn kill_service(&mut self, service: UnitId, signal: Signal) {
+    let cancel_token = CancellationToken::new();
+    let future = systemd::kill_service(service.clone(), signal, cancel_token.clone());
+    self.service_action(service, "Kill".into(), systemd::kill_verb(signal), cancel_token, future);
   }
 
   fn restart_service(&mut self, service: UnitId) {
     let cancel_token = CancellationToken::new();
     let future = systemd::restart_service(service.clone(), cancel_token.clone());
-    self.service_action(service, "Restart".into(), cancel_token, future);
+    self.service_action(service, "Restart".into(), "restart".into(), cancel_token, future);
   }
 
-  fn service_action<Fut>(&mut self, service: UnitId, action_name: String, cancel_token: CancellationToken, action: Fut)
-  where
-    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
-  {
-    let tx = self.action_tx.clone().unwrap();
+  fn reload_service(&mut self, service: UnitId) {
+    let cancel_token = CancellationToken::new();
+    let future = systemd::reload_service(service.clone(), cancel_token.clone());
+    self.service_action(service, "Reload".into(), "reload".into(), cancel_token, future);
+  }
 
-    self.cancel_token = Some(cancel_token.clone());
+  fn enable_service(&mut self, service: UnitId) {
+    let cancel_token = CancellationToken::new();
+    let future = systemd::enable_service(service.clone(), cancel_token.clone());
+    self.service_action(service, "Enable".into(), "enable".into(), cancel_token, future);
+  }
 
-    let tx_clone = tx.clone();
-    let spinner_task = tokio::spawn(async move {
-      let mut interval = tokio::time::interval(Duration::from_millis(200));
-      loop {
-        interval.tick().await;
-        tx_clone.send(Action::SpinnerTick).unwrap();
-      }
-    });
+  fn disable_service(&mut self, service: UnitId) {
+    let cancel_token = CancellationToken::new();
+    let future = systemd::disable_service(service.clone(), cancel_token.clone());
+    self.service_action(service, "Disable".into(), "disable".into(), cancel_token, future);
+  }
 
-    tokio::spawn(async move {
-      tx.send(Action::EnterMode(Mode::Processing)).unwrap();
-      match action.await {
-        Ok(_) => {
-          info!("{} of {:?} service {} succeeded", action_name, service.scope, service.name);
-          tx.send(Action::EnterMode(Mode::ServiceList)).unwrap();
-        },
-        // would be nicer to check the error type here, but this is easier
-        Err(_) if cancel_token.is_cancelled() => {
-          warn!("{} of {:?} service {} was cancelled", action_name, service.scope, service.name)
-        },
-        Err(e) => {
-          error!("{} of {:?} service {} failed: {}", action_name, service.scope, service.name, e);
-          let mut error_string = e.to_string();
+  // Wrap an action on the selected unit so it has to be confirmed first, unless confirmations are turned off
+  fn confirm_destructive(&self, verb: &str, action: Action) -> Action {
+    let Some(selected) = self.filtered_units.selected() else { return action };
+    if self.config.skip_confirmations {
+      return action;
+    }
+    Action::RequestConfirmation(Confirmation {
+      prompt: format!("{} {}?", verb, selected.name),
+      items: vec![],
+      action: Box::new(action),
+      cancel_mode: Mode::ActionMenu,
+    })
+  }
 
-          if error_string.contains("AccessDenied") {
-            error_string.push('\n');
-            error_string.push('\n');
-            error_string.push_str("Try running this tool with sudo.");
-          }
+  // Confirm enabling/disabling the marked unit files, or the highlighted one if none are marked
+  fn confirm_batch_set_enabled(&self, enable: bool) -> Vec<Action> {
+    let mut units =
+      self.unit_files.items.iter().map(|f| f.id()).filter(|id| self.marked_unit_files.contains(id)).collect_vec();
+    if units.is_empty() {
+      units.extend(self.unit_files.selected().map(|f| f.id()));
+    }
+    if units.is_empty() {
+      return vec![];
+    }
 
-          tx.send(Action::EnterError { err: error_string }).unwrap();
-        },
-      }
-      spinner_task.abort();
-      tx.send(Action::RefreshServices).unwrap();
+    let verb = if enable { "Enable" } else { "Disable" };
+    let confirmation = Confirmation {
+      prompt: format!("{} {} unit file(s)?", verb, units.len()),
+      items: units.iter().map(|u| u.name.clone()).collect(),
+      action: Box::new(Action::BatchSetEnabled { units, enable }),
+      cancel_mode: Mode::UnitFiles,
+    };
+    vec![Action::RequestConfirmation(confirmation)]
+  }
 
-      // Refresh a bit more frequently after a service action
-      for _ in 0..3 {
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        tx.send(Action::RefreshServices).unwrap();
-      }
-    });
+  // Stopping or restarting several units at once is asked about even more than a single one
+  fn confirm_batch(&self, action: BatchAction, units: Vec<UnitId>, cancel_mode: Mode) -> Action {
+    let prompt = format!("{} {} units?", action.verb(), units.len());
+    let items = units.iter().map(|u| u.name.clone()).collect();
+    let batch = Action::BatchServiceAction { units, action };
+    if action == BatchAction::Start || self.config.skip_confirmations {
+      return batch;
+    }
+    Action::RequestConfirmation(Confirmation { prompt, items, action: Box::new(batch), cancel_mode })
   }
-}
 
-impl Component for Home {
-  fn init(&mut self, tx: UnboundedSender<Action>) -> anyhow::Result<()> {
-    self.action_tx = Some(tx.clone());
-    // TODO find a better name for these. They're used to run any async data loading that needs to happen after the selection is changed,
-    // not just journalctl stuff
-    let (journalctl_tx, journalctl_rx) = std::sync::mpsc::channel::<UnitId>();
-    self.journalctl_tx = Some(journalctl_tx);
+  // The action menu for the selected unit, offering what makes sense in its current state
+  fn unit_menu_items(&self) -> Option<Vec<MenuItem>> {
+    let unit = self.filtered_units.selected()?;
+    let selected = unit.id();
+    // anything that isn't fully stopped can be stopped, e.g. a unit stuck activating
+    let running = !matches!(unit.active_state.as_str(), "inactive" | "failed");
+    // not known until the unit's properties have loaded, offer both in the meantime
+    let file_state = unit.properties.as_ref().and_then(|p| p.unit_file_state.as_deref());
+    let (can_enable, can_disable) = match file_state {
+      Some("enabled" | "enabled-runtime") => (false, true),
+      Some("disabled") => (true, false),
+      // static, indirect, generated, transient... units can't be enabled or disabled
+      Some(_) => (false, false),
+      None => (true, true),
+    };
 
-    // TODO: move into function
-    tokio::task::spawn_blocking(move || {
+    let mut menu_items = vec![];
+    if unit.is_masked() {
+      menu_items.push(MenuItem::new("Unmask", Action::UnmaskService(selected.clone())));
+    } else if running {
+      menu_items.extend([
+        MenuItem::new("Stop", self.confirm_destructive("Stop", Action::StopService(selected.clone()))),
+        MenuItem::new("Restart", self.confirm_destructive("Restart", Action::RestartService(selected.clone()))),
+        MenuItem::new("Reload", Action::ReloadService(selected.clone())),
+        MenuItem::new("Kill...", Action::ViewKillSignals(selected.clone())),
+      ]);
+    } else {
+      menu_items.push(MenuItem::new("Start", Action::StartService(selected.clone())));
+    }
+    if !unit.is_masked() {
+      if can_enable {
+        menu_items.push(MenuItem::new("Enable", Action::EnableService(selected.clone())));
+      }
+      if can_disable {
+        let disable = self.confirm_destructive("Disable", Action::DisableService(selected.clone()));
+        menu_items.push(MenuItem::new("Disable", disable));
+      }
+      menu_items.push(MenuItem::new("Mask", self.confirm_destructive("Mask", Action::MaskService(selected.clone()))));
+    }
+
+    menu_items.extend([
+      MenuItem::new(
+        "Restart with dependencies",
+        Action::RestartWithDependencies { unit: selected.clone(), reverse: false },
+      ),
+      MenuItem::new(
+        "Restart with dependents",
+        Action::RestartWithDependencies { unit: selected.clone(), reverse: true },
+      ),
+      MenuItem::new("Copy unit file path to clipboard", Action::CopyUnitFilePath),
+      MenuItem::new("Copy error/warning logs to clipboard", Action::CopyErrorLogs),
+      MenuItem::new("View drop-in overrides", Action::ViewDropIns),
+      MenuItem::new("Diff overrides against the vendor unit file", Action::ViewUnitDiff),
+      MenuItem::new("Edit note", Action::EnterMode(Mode::EditNote)),
+      MenuItem::new("Open a shell with the unit's environment", Action::OpenEnvironmentShell),
+      MenuItem::new(
+        if self.logs_current_invocation_only {
+          "Show logs from all invocations"
+        } else {
+          "Show logs from current invocation only"
+        },
+        Action::ToggleInvocationLogs,
+      ),
+    ]);
+
+    Some(menu_items)
+  }
+
+  // Match the command palette's items against what's typed, best match first
+  fn filter_palette(&mut self) {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let query = self.palette_input.value();
+    let mut matches = self
+      .palette_items
+      .iter()
+      .enumerate()
+      .filter_map(|(i, item)| {
+        let score = if query.is_empty() { Some(0) } else { matcher.fuzzy_match(&item.name, query) };
+        score.map(|score| (score, i))
+      })
+      .collect_vec();
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+    self.palette_matches = StatefulList::with_items(matches.into_iter().map(|(_, i)| i).collect());
+    if !self.palette_matches.items.is_empty() {
+      self.palette_matches.select(Some(0));
+    }
+  }
+
+  // What a command from the keymap (or the command palette) does in the unit list
+  fn run_command(&mut self, command: Command) -> Vec<Action> {
+    match command {
+      Command::Quit => vec![Action::Quit],
+      Command::Up => {
+        // if we're filtering the list, and we're at the top, and there's text in the search box, go to search mode
+        if self.filtered_units.state.selected() == Some(0) {
+          return vec![Action::EnterMode(Mode::Search)];
+        }
+
+        self.previous();
+        vec![Action::Render]
+      },
+      Command::Down => {
+        self.next();
+        vec![Action::Render]
+      },
+      Command::Top => {
+        self.select(Some(0));
+        vec![Action::Render]
+      },
+      Command::Bottom => {
+        let last = self.filtered_units.items.len().checked_sub(1);
+        self.select(last);
+        vec![Action::Render]
+      },
+      Command::ToggleFailedOnly => vec![Action::ToggleFailedOnly],
+      Command::RestartFailed => {
+        let failed =
+          self.all_units.values().filter(|u| u.is_failed() || u.sub_state == "failed").map(|u| u.id()).collect_vec();
+        if failed.is_empty() {
+          return vec![Action::SetStatusMessage("No failed units".into())];
+        }
+        vec![self.confirm_batch(BatchAction::Restart, failed, Mode::ServiceList)]
+      },
+      Command::NextFailed => {
+        // the next failed unit after the selection, wrapping around
+        let units = &self.filtered_units.items;
+        let start = self.filtered_units.state.selected().map_or(0, |i| i + 1);
+        let next_failed = (0..units.len())
+          .map(|i| (start + i) % units.len())
+          .find(|&i| units[i].is_failed() || units[i].sub_state == "failed");
+        match next_failed {
+          Some(index) => {
+            self.select(Some(index));
+            vec![Action::Render]
+          },
+          None => vec![Action::SetStatusMessage("No failed units".into())],
+        }
+      },
+      Command::Search if self.logs_focused => vec![Action::EnterMode(Mode::LogSearch)],
+      Command::Search => vec![Action::EnterMode(Mode::Search)],
+      Command::NextLogMatch => vec![Action::NextLogMatch],
+      Command::PreviousLogMatch => vec![Action::PreviousLogMatch],
+      Command::StateChanges => vec![Action::EnterMode(Mode::StateChanges)],
+      Command::ToggleCurrentBootLogs => vec![Action::ToggleCurrentBootLogs],
+      Command::CycleLogPriority => vec![Action::CycleLogPriority],
+      Command::ViewBoots => vec![Action::ViewBoots],
+      Command::ViewUnitFiles => vec![Action::ViewUnitFiles],
+      Command::ReloadLogs => vec![Action::ReloadLogs],
+      Command::DaemonReload => vec![Action::DaemonReload],
+      Command::LoadMoreLogs => vec![Action::LoadMoreLogs],
+      Command::ToggleDetails => vec![Action::ToggleDetailsExpanded],
+      Command::ToggleRecentErrors => vec![Action::ToggleRecentErrorsFilter],
+      Command::ToggleLogsFocus => vec![Action::ToggleLogsFocus],
+      Command::CopyFilterCommand => vec![Action::CopyFilterCommand],
+      Command::ToggleAgeColumn => vec![Action::ToggleAgeColumn],
+      Command::ToggleFullNames => vec![Action::ToggleFullNames],
+      Command::ToggleFollowLogs => vec![Action::ToggleFollowLogs],
+      Command::TogglePauseLogs => vec![Action::TogglePauseLogs],
+      Command::ViewFullDetails => vec![Action::EnterMode(Mode::DetailsFull)],
+      Command::ViewUnitCat => vec![Action::ViewUnitCat],
+      Command::EditUnitFile => vec![Action::EditUnitFile],
+      Command::ViewStatus => vec![Action::ViewStatus],
+      Command::ViewDependencies => vec![Action::ViewDependencies],
+      Command::CopyLogs => vec![Action::CopyLogs],
+      Command::ExportLogs => vec![Action::ExportLogs],
+      Command::CycleScope => vec![Action::CycleScope],
+      Command::CycleUnitType => vec![Action::CycleUnitType],
+      Command::InvertFilter => vec![Action::ToggleInvertFilter],
+      Command::ToggleLogBookmark => vec![Action::ToggleLogBookmark],
+      Command::NextLogBookmark => vec![Action::NextLogBookmark],
+      Command::ToggleChildLogs => vec![Action::ToggleChildLogs],
+      Command::ToggleLogWrap => vec![Action::ToggleLogWrap],
+      Command::ToggleSplit => vec![Action::ToggleSplit],
+      Command::ScrollLogsLeft if self.logs_focused => vec![Action::ScrollLeft(8), Action::Render],
+      Command::ScrollLogsRight if self.logs_focused => vec![Action::ScrollRight(8), Action::Render],
+      // in the unit list, these expand and collapse template groups
+      Command::ScrollLogsRight => match self.selected_group() {
+        Some(group) => {
+          self.set_group_expanded(group, true);
+          vec![Action::Render]
+        },
+        None => vec![],
+      },
+      Command::ScrollLogsLeft => {
+        // from an instance, collapse the group it's in
+        let group = self.selected_service().and_then(|id| {
+          let unit = self.all_units.get(&id)?;
+          Some(UnitId { name: unit.template()?, scope: unit.scope })
+        });
+        match group.or_else(|| self.selected_group()) {
+          Some(group) if self.expanded_groups.contains(&group) => {
+            self.set_group_expanded(group, false);
+            vec![Action::Render]
+          },
+          _ => vec![],
+        }
+      },
+      Command::ActionMenu => match self.selected_group() {
+        Some(group) => {
+          let expanded = self.expanded_groups.contains(&group);
+          self.set_group_expanded(group, !expanded);
+          vec![Action::Render]
+        },
+        None => vec![Action::EnterMode(Mode::ActionMenu)],
+      },
+      Command::ToggleGroupInstances => vec![Action::ToggleGroupInstances],
+      Command::ToggleMark => {
+        // a template row isn't a unit that can be acted on
+        if let Some(id) = self.selected_service().filter(|id| !self.instance_groups.contains_key(id)) {
+          if !self.marked_units.remove(&id) {
+            self.marked_units.insert(id);
+          }
+        }
+        self.next();
+        vec![Action::Render]
+      },
+      Command::CommandPalette => vec![Action::EnterMode(Mode::CommandPalette)],
+      Command::ClearMarks => {
+        self.marked_units.clear();
+        vec![Action::Render]
+      },
+    }
+  }
+
+  // `verb` is what's passed to systemctl for the action, shown in dry runs
+  fn service_action<Fut>(
+    &mut self,
+    service: UnitId,
+    action_name: String,
+    verb: String,
+    cancel_token: CancellationToken,
+    action: Fut,
+  ) where
+    Fut: Future<Output = Result<(), SystemdError>> + Send + 'static,
+  {
+    let tx = self.action_tx.clone().unwrap();
+
+    self.cancel_token = Some(cancel_token.clone());
+    // whatever the action does will show up in the logs, don't go back to ones from before it
+    self.log_cache.shift_remove(&service);
+
+    let spinner_task = spawn_spinner(tx.clone());
+
+    tokio::spawn(async move {
+      tx.send(Action::EnterMode(Mode::Processing)).unwrap();
+      let result = action.await;
+      // the dry run message and errors are more important than how the refresh is going
+      let show_progress = result.is_ok() && !systemd::is_dry_run();
+      if !systemd::is_dry_run() && !matches!(result, Err(SystemdError::Cancelled)) {
+        let last_action =
+          LastAction { name: action_name.clone(), time: chrono::Local::now(), succeeded: result.is_ok() };
+        tx.send(Action::SetLastAction { unit: service.clone(), action: last_action }).unwrap();
+      }
+      match result {
+        Ok(_) => {
+          info!("{} of {:?} service {} succeeded", action_name, service.scope, service.name);
+          tx.send(Action::EnterMode(Mode::ServiceList)).unwrap();
+          if systemd::is_dry_run() {
+            let command = systemd::systemctl_command(&verb, &service);
+            tx.send(Action::SetStatusMessage(format!("Dry run: would have run `{}`", command))).unwrap();
+          }
+        },
+        Err(SystemdError::Cancelled) => {
+          warn!("{} of {:?} service {} was cancelled", action_name, service.scope, service.name)
+        },
+        Err(e) => {
+          error!("{} of {:?} service {} failed: {}", action_name, service.scope, service.name, e);
+          let err = match e {
+            SystemdError::PermissionDenied(_) => {
+              format!("{e}\n\nTry running this tool with sudo, or run a polkit authentication agent to get a prompt.")
+            },
+            _ => e.to_string(),
+          };
+
+          tx.send(Action::EnterError { err }).unwrap();
+        },
+      }
+      spinner_task.abort();
+
+      // Refresh a bit more frequently after a service action
+      for i in 1..=POST_ACTION_REFRESHES {
+        if i > 1 {
+          tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        if show_progress {
+          tx.send(Action::SetStatusMessage(format!("Refreshing… {i}/{POST_ACTION_REFRESHES}"))).unwrap();
+        }
+        tx.send(Action::RefreshServices).unwrap();
+      }
+
+      if show_progress {
+        // say where the unit ended up once things settled, e.g. that it's still activating rather than active
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let names = std::slice::from_ref(&service.name);
+        if let Ok(units) = systemd::get_services(service.scope, UnitType::All, names).await {
+          if let Some(unit) = units.first() {
+            let message = format!("{} is {} ({})", unit.name, unit.active_state, unit.sub_state);
+            tx.send(Action::SetServices(units)).unwrap();
+            tx.send(Action::SetStatusMessage(message)).unwrap();
+          }
+        }
+      }
+    });
+  }
+
+  /// Stop the journalctl dispatcher and any `journalctl --follow` process so nothing outlives the app
+  pub fn shutdown(&mut self) {
+    if let Some(token) = self.log_dispatcher_token.take() {
+      token.cancel();
+    }
+    // dropping the sender wakes the dispatcher thread up so it can exit
+    self.journalctl_tx = None;
+    if let Some(cancel_token) = self.cancel_token.take() {
+      cancel_token.cancel();
+    }
+  }
+
+  // Spawns the thread that loads logs (and other data) for the selected unit.
+  // Calling this again replaces the previous thread; the old one exits once its sender is dropped.
+  fn spawn_log_dispatcher(&mut self) {
+    let tx = self.action_tx.clone().unwrap();
+    let extra_args = self.config.journalctl_extra_args.clone();
+    // TODO find a better name for these. They're used to run any async data loading that needs to happen after the selection is changed,
+    // not just journalctl stuff
+    let (journalctl_tx, journalctl_rx) = std::sync::mpsc::channel::<LogQuery>();
+    self.journalctl_tx = Some(journalctl_tx);
+    // stop the previous dispatcher's follow task right away instead of waiting for it to notice the dropped sender
+    if let Some(token) = self.log_dispatcher_token.take() {
+      token.cancel();
+    }
+    let shutdown_token = CancellationToken::new();
+    self.log_dispatcher_token = Some(shutdown_token.clone());
+
+    tokio::task::spawn_blocking(move || {
       let mut last_follow_handle: Option<JoinHandle<()>> = None;
 
       loop {
-        let mut unit: UnitId = match journalctl_rx.recv() {
-          Ok(unit) => unit,
-          Err(_) => return,
+        let mut query: LogQuery = match journalctl_rx.recv() {
+          Ok(query) => query,
+          Err(_) => {
+            // the sender was dropped, either because we're shutting down or reinitializing
+            if let Some(handle) = last_follow_handle.take() {
+              handle.abort();
+            }
+            return;
+          },
         };
 
         // drain the channel, use the last value
-        while let Ok(service) = journalctl_rx.try_recv() {
-          info!("Skipping logs for {}...", unit.name);
-          unit = service;
+        while let Ok(next_query) = journalctl_rx.try_recv() {
+          info!("Skipping logs for {}...", query.unit.name);
+          query = next_query;
         }
+        let unit = query.unit.clone();
 
         if let Some(handle) = last_follow_handle.take() {
           info!("Cancelling previous journalctl task");
@@ -359,26 +1429,28 @@ impl Component for Home {
         // lazy debounce to avoid spamming journalctl on slow connections/systems
         std::thread::sleep(Duration::from_millis(100));
 
-        // get the unit file path
-        match systemd::get_unit_file_location(&unit) {
-          Ok(path) => {
-            let _ = tx.send(Action::SetUnitFilePath { unit: unit.clone(), path });
+        // get the unit file path, invocation ID, etc.
+        let properties = match systemd::get_unit_properties(&unit) {
+          Ok(properties) => {
+            let _ = tx.send(Action::SetUnitProperties { unit: unit.clone(), properties: Box::new(properties.clone()) });
             let _ = tx.send(Action::Render);
+            properties
           },
-          Err(e) => error!("Error getting unit file path for {}: {}", unit.name, e),
-        }
+          Err(e) => {
+            error!("Error getting unit properties for {}: {}", unit.name, e);
+            UnitProperties::default()
+          },
+        };
+
+        let filter_args = journalctl_filter_args(&query, &properties);
 
         // First, get the N lines in a batch
         info!("Getting logs for {}", unit.name);
         let start = std::time::Instant::now();
 
-        let mut args = vec!["--quiet", "--output=short-iso", "--lines=500", "-u"];
-
-        args.push(&unit.name);
-
-        if unit.scope == UnitScope::User {
-          args.push("--user");
-        }
+        let mut args = vec!["--quiet".to_string(), "--output=short-iso".into(), format!("--lines={}", query.lines)];
+        args.extend(filter_args.iter().cloned());
+        args.extend(extra_args.iter().cloned());
 
         match cmd("journalctl", args).read() {
           Ok(stdout) => {
@@ -392,44 +1464,117 @@ impl Component for Home {
             let _ = tx.send(Action::SetLogs { unit: unit.clone(), logs });
             let _ = tx.send(Action::Render);
           },
-          Err(e) => warn!("Error getting logs for {}: {}", unit.name, e),
+          Err(e) => {
+            warn!("Error getting logs for {}: {}, falling back to systemctl status", unit.name, e);
+            match systemd::get_status_log_tail(&unit, query.lines) {
+              Ok(logs) => {
+                let _ = tx.send(Action::SetStatusLogs { unit: unit.clone(), logs });
+                let _ = tx.send(Action::Render);
+              },
+              Err(e) => warn!("Error getting status logs for {}: {}", unit.name, e),
+            }
+            // following needs a working journalctl too
+            continue;
+          },
         }
 
         // Then follow the logs
         // Splitting this into two commands is a bit of a hack that makes it easier to get the initial batch of logs
         // This does mean that we'll miss any logs that are written between the two commands, low enough risk for now
+        if shutdown_token.is_cancelled() {
+          return;
+        }
+        if !query.follow {
+          continue;
+        }
+
         let tx = tx.clone();
+        let shutdown_token = shutdown_token.clone();
+        let extra_args = extra_args.clone();
         last_follow_handle = Some(tokio::spawn(async move {
-          let mut command = tokio::process::Command::new("journalctl");
-          command.arg("-u");
-          command.arg(unit.name.clone());
-          command.arg("--output=short-iso");
-          command.arg("--follow");
-          command.arg("--lines=0");
-          command.arg("--quiet");
+          let mut args = filter_args;
+          args.extend(["--output=short-iso".into(), "--follow".into(), "--lines=0".into(), "--quiet".into()]);
+          args.extend(extra_args.iter().cloned());
+          // through ssh with --host, killing it ends the remote journalctl as well once it next writes
+          let mut command = systemd::tokio_cmd("journalctl", args);
           command.stdout(Stdio::piped());
           command.stderr(Stdio::piped());
+          // aborting this task drops the child; make sure that doesn't leave an orphaned journalctl behind
+          command.kill_on_drop(true);
 
-          if unit.scope == UnitScope::User {
-            command.arg("--user");
-          }
-
-          let mut child = command.spawn().expect("failed to execute process");
+          let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+              error!("Failed to start journalctl --follow for {}: {}", unit.name, e);
+              let _ = tx.send(Action::EnterError { err: format!("Failed to follow logs for {}: {}", unit.name, e) });
+              return;
+            },
+          };
 
           let stdout = child.stdout.take().unwrap();
+          let stderr = child.stderr.take().unwrap();
+
+          // journalctl reports problems like bad filters on stderr, show them instead of silently dropping them
+          let stderr_tx = tx.clone();
+          let stderr_unit = unit.clone();
+          tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+              warn!("journalctl stderr for {}: {}", stderr_unit.name, line);
+              let line = format!("{}{}", JOURNALCTL_ERROR_PREFIX, line);
+              let _ = stderr_tx.send(Action::AppendLogLine { unit: stderr_unit.clone(), line });
+              let _ = stderr_tx.send(Action::Render);
+            }
+          });
 
           let reader = tokio::io::BufReader::new(stdout);
           let mut lines = reader.lines();
-          while let Some(line) = lines.next_line().await.unwrap() {
-            let _ = tx.send(Action::AppendLogLine { unit: unit.clone(), line });
-            let _ = tx.send(Action::Render);
+          loop {
+            tokio::select! {
+              _ = shutdown_token.cancelled() => break,
+              line = lines.next_line() => match line {
+                Ok(Some(line)) => {
+                  let _ = tx.send(Action::AppendLogLine { unit: unit.clone(), line });
+                  let _ = tx.send(Action::Render);
+                },
+                Ok(None) => break,
+                Err(e) => {
+                  warn!("Failed to read journalctl output for {}: {}", unit.name, e);
+                  break;
+                },
+              },
+            }
           }
+          let _ = child.kill().await;
         }));
       }
     });
+  }
+}
+
+impl Component for Home {
+  fn init(&mut self, tx: UnboundedSender<Action>) -> anyhow::Result<()> {
+    self.action_tx = Some(tx.clone());
+    // not being able to read notes etc. shouldn't stop the app from starting
+    self.state = PersistedState::load().unwrap_or_else(|e| {
+      error!("Failed to load persisted state: {:#}", e);
+      PersistedState::default()
+    });
+    self.split = self.state.split.unwrap_or(self.config.split);
+    self.spawn_log_dispatcher();
     Ok(())
   }
 
+  fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Vec<Action> {
+    let (column, row) = (mouse.column, mouse.row);
+    match mouse.kind {
+      MouseEventKind::Down(MouseButton::Left) => vec![Action::MouseClick { column, row }],
+      MouseEventKind::ScrollDown => vec![Action::MouseScroll { column, row, down: true }],
+      MouseEventKind::ScrollUp => vec![Action::MouseScroll { column, row, down: false }],
+      _ => vec![],
+    }
+  }
+
   fn handle_key_events(&mut self, key: KeyEvent) -> Vec<Action> {
     if key.modifiers.contains(KeyModifiers::CONTROL) {
       match key.code {
@@ -438,9 +1583,10 @@ impl Component for Home {
         KeyCode::Char('z') => return vec![Action::Suspend],
         KeyCode::Char('f') => return vec![Action::EnterMode(Mode::Search)],
         KeyCode::Char('l') => return vec![Action::ToggleShowLogger],
-        // vim keybindings, apparently
-        KeyCode::Char('d') => return vec![Action::ScrollDown(1), Action::Render],
-        KeyCode::Char('u') => return vec![Action::ScrollUp(1), Action::Render],
+        KeyCode::Char('r') => return vec![Action::Reinitialize],
+        // vim keybindings, apparently: half a page at a time
+        KeyCode::Char('d') => return vec![Action::ScrollDown((self.logs_viewport_height / 2).max(1)), Action::Render],
+        KeyCode::Char('u') => return vec![Action::ScrollUp((self.logs_viewport_height / 2).max(1)), Action::Render],
         _ => (),
       }
     }
@@ -461,37 +1607,209 @@ impl Component for Home {
 
     match self.mode {
       Mode::ServiceList => {
-        match key.code {
-          KeyCode::Char('q') => vec![Action::Quit],
-          KeyCode::Up | KeyCode::Char('k') => {
-            // if we're filtering the list, and we're at the top, and there's text in the search box, go to search mode
-            if self.filtered_units.state.selected() == Some(0) {
-              return vec![Action::EnterMode(Mode::Search)];
-            }
-
-            self.previous();
-            vec![Action::Render]
-          },
-          KeyCode::Down | KeyCode::Char('j') => {
-            self.next();
-            vec![Action::Render]
-          },
-          KeyCode::Char('/') => vec![Action::EnterMode(Mode::Search)],
-          KeyCode::Enter | KeyCode::Char(' ') => vec![Action::EnterMode(Mode::ActionMenu)],
-          _ => vec![],
-        }
+        let Some(&command) = self.keymap.get(&KeyBinding::from(key)) else { return vec![] };
+        self.run_command(command)
       },
       Mode::Help => match key.code {
         KeyCode::Esc | KeyCode::Enter => vec![Action::ToggleHelp],
+        // clamped to the help's length when it's rendered
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.help_scroll = self.help_scroll.saturating_add(1);
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.help_scroll = self.help_scroll.saturating_sub(1);
+          vec![Action::Render]
+        },
         _ => vec![],
       },
       Mode::Error => match key.code {
         KeyCode::Esc | KeyCode::Enter => vec![Action::EnterMode(Mode::ServiceList)],
         _ => vec![],
       },
+      Mode::DropIns => match key.code {
+        KeyCode::Esc => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.drop_ins.next();
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.drop_ins.previous();
+          vec![Action::Render]
+        },
+        KeyCode::Enter => match self.drop_ins.selected() {
+          Some(path) => vec![command_from_env("PAGER", "less", path)],
+          None => vec![],
+        },
+        KeyCode::Char('e') => match self.drop_ins.selected() {
+          Some(path) => vec![command_from_env("EDITOR", "vi", path)],
+          None => vec![],
+        },
+        _ => vec![],
+      },
+      Mode::UnitFiles => match key.code {
+        KeyCode::Esc | KeyCode::Char('U') => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.unit_files.next();
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.unit_files.previous();
+          vec![Action::Render]
+        },
+        KeyCode::Char(' ') => {
+          if let Some(id) = self.unit_files.selected().map(|f| f.id()) {
+            if !self.marked_unit_files.remove(&id) {
+              self.marked_unit_files.insert(id);
+            }
+          }
+          self.unit_files.next();
+          vec![Action::Render]
+        },
+        KeyCode::Char('e') => self.confirm_batch_set_enabled(true),
+        KeyCode::Char('d') => self.confirm_batch_set_enabled(false),
+        _ => vec![],
+      },
+      Mode::Boots => match key.code {
+        KeyCode::Esc | KeyCode::Char('B') => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.boots.next();
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.boots.previous();
+          vec![Action::Render]
+        },
+        KeyCode::Enter => match self.boots.selected() {
+          Some(boot) => vec![Action::SetLogsBoot(boot.as_ref().map(|b| b.offset))],
+          None => vec![],
+        },
+        _ => vec![],
+      },
+      Mode::DetailsFull => match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('V') => vec![Action::EnterMode(Mode::ServiceList)],
+        _ => vec![],
+      },
+      Mode::UnitDiff => match key.code {
+        KeyCode::Esc | KeyCode::Enter => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.unit_diff_scroll = self.unit_diff_scroll.saturating_add(1);
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.unit_diff_scroll = self.unit_diff_scroll.saturating_sub(1);
+          vec![Action::Render]
+        },
+        _ => vec![],
+      },
+      Mode::UnitCat => match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('c') => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.unit_cat_scroll = self.unit_cat_scroll.saturating_add(1);
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.unit_cat_scroll = self.unit_cat_scroll.saturating_sub(1);
+          vec![Action::Render]
+        },
+        _ => vec![],
+      },
+      Mode::Status => match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('s') => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.status_scroll = self.status_scroll.saturating_add(1);
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.status_scroll = self.status_scroll.saturating_sub(1);
+          vec![Action::Render]
+        },
+        _ => vec![],
+      },
+      Mode::Dependencies => match key.code {
+        KeyCode::Esc | KeyCode::Char('D') => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.dependencies.next();
+          vec![Action::Render]
+        },
+        KeyCode::Up | KeyCode::Char('k') => {
+          self.dependencies.previous();
+          vec![Action::Render]
+        },
+        KeyCode::Enter => match (&self.dependencies_of, self.dependencies.selected()) {
+          (Some(of), Some((_, name))) => vec![Action::GoToUnit(UnitId { name: name.clone(), scope: of.scope })],
+          _ => vec![Action::EnterMode(Mode::ServiceList)],
+        },
+        _ => vec![],
+      },
+      Mode::CommandPalette => match key.code {
+        KeyCode::Esc => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Down => {
+          self.palette_matches.next();
+          vec![Action::Render]
+        },
+        KeyCode::Up => {
+          self.palette_matches.previous();
+          vec![Action::Render]
+        },
+        KeyCode::Enter => {
+          let action = self.palette_matches.selected().map(|&i| self.palette_items[i].action.clone());
+          // leave first, the action may well open something else
+          self.mode = Mode::ServiceList;
+          action.into_iter().chain([Action::Render]).collect()
+        },
+        _ => {
+          self.palette_input.handle_event(&crossterm::event::Event::Key(key));
+          self.filter_palette();
+          vec![Action::Render]
+        },
+      },
+      Mode::EditNote => match key.code {
+        KeyCode::Esc => vec![Action::EnterMode(Mode::ServiceList)],
+        KeyCode::Enter => vec![Action::SaveNote],
+        _ => {
+          self.note_input.handle_event(&crossterm::event::Event::Key(key));
+          vec![Action::Render]
+        },
+      },
+      Mode::Confirm => match key.code {
+        KeyCode::Char('y') | KeyCode::Enter => vec![Action::Confirm],
+        KeyCode::Char('n') | KeyCode::Esc => {
+          // straight back, without EnterMode resetting the action menu
+          self.mode = self.confirmation.take().map(|c| c.cancel_mode).unwrap_or(Mode::ServiceList);
+          vec![Action::Render]
+        },
+        _ => vec![],
+      },
+      Mode::StateChanges => match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('T') => vec![Action::EnterMode(Mode::ServiceList)],
+        _ => vec![],
+      },
+      Mode::LogSearch => match key.code {
+        KeyCode::Esc => {
+          self.log_search.reset();
+          vec![Action::EnterMode(Mode::ServiceList)]
+        },
+        KeyCode::Enter => vec![Action::EnterMode(Mode::ServiceList)],
+        _ => {
+          self.log_search.handle_event(&crossterm::event::Event::Key(key));
+          // search as you type, starting from where the logs are scrolled to
+          self.jump_to_log_match(true, true);
+          vec![Action::Render]
+        },
+      },
       Mode::Search => match key.code {
+        // anything that leaves the search box acts on the list, so it has to be up to date with the search first
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Down | KeyCode::Tab | KeyCode::Up if self.filter_pending => {
+          self.filter_pending = false;
+          self.refresh_filtered_units();
+          self.handle_key_events(key)
+        },
         KeyCode::Esc => vec![Action::EnterMode(Mode::ServiceList)],
-        KeyCode::Enter => vec![Action::EnterMode(Mode::ActionMenu)],
+        KeyCode::Enter => match self.config.search_enter {
+          SearchEnter::ActionMenu => vec![Action::EnterMode(Mode::ActionMenu)],
+          SearchEnter::ConfirmSearch => vec![Action::EnterMode(Mode::ServiceList)],
+        },
         KeyCode::Down | KeyCode::Tab => {
           self.next();
           vec![Action::EnterMode(Mode::ServiceList)]
@@ -504,9 +1822,10 @@ impl Component for Home {
           let prev_search_value = self.input.value().to_owned();
           self.input.handle_event(&crossterm::event::Event::Key(key));
 
-          // if the search value changed, filter the list
+          // if the search value changed, filter the list once typing pauses. The input is drawn right away
           if prev_search_value != self.input.value() {
-            self.refresh_filtered_units();
+            self.filter_pending = true;
+            return vec![Action::DebouncedFilter, Action::Render];
           }
           vec![Action::Render]
         },
@@ -536,33 +1855,72 @@ impl Component for Home {
 
   fn dispatch(&mut self, action: Action) -> Option<Action> {
     match action {
+      Action::FilterUnits => {
+        // already done if the search box was left in the meantime
+        if self.filter_pending {
+          self.filter_pending = false;
+          self.refresh_filtered_units();
+        }
+        return Some(Action::Render);
+      },
       Action::ToggleShowLogger => {
         self.show_logger = !self.show_logger;
         return Some(Action::Render);
       },
       Action::EnterMode(mode) => {
-        if mode == Mode::ActionMenu {
-          let selected = match self.filtered_units.selected() {
-            Some(s) => s.id(),
-            None => return None,
-          };
-
-          // TODO: use current status to determine which actions are available?
-          let menu_items = vec![
-            MenuItem::new("Start", Action::StartService(selected.clone())),
-            MenuItem::new("Stop", Action::StopService(selected.clone())),
-            MenuItem::new("Restart", Action::RestartService(selected.clone())),
-            MenuItem::new("Copy unit file path to clipboard", Action::CopyUnitFilePath),
-            // TODO add these
-            // MenuItem::new("Reload", Action::ReloadService(selected.clone())),
-            // MenuItem::new("Enable", Action::EnableService(selected.clone())),
-            // MenuItem::new("Disable", Action::DisableService(selected.clone())),
-          ];
+        if mode != Mode::Processing {
+          self.processing_status = None;
+        }
 
+        if mode == Mode::ActionMenu && !self.marked_units.is_empty() {
+          // in list order, including marked units that the search is hiding right now
+          let units = self.all_units.keys().filter(|id| self.marked_units.contains(id)).cloned().collect_vec();
+          let menu_items = BatchAction::ALL
+            .into_iter()
+            .map(|action| {
+              let name = format!("{} {} marked units", action.verb(), units.len());
+              MenuItem::new(&name, self.confirm_batch(action, units.clone(), Mode::ActionMenu))
+            })
+            .collect_vec();
+          self.menu_items = StatefulList::with_items(menu_items);
+          self.menu_items.state.select(Some(0));
+        } else if mode == Mode::CommandPalette {
+          // the selected unit's actions first, then everything from the unit list
+          let mut items = vec![];
+          if let (Some(unit), Some(menu_items)) = (self.filtered_units.selected(), self.unit_menu_items()) {
+            items.extend(menu_items.into_iter().map(|item| {
+              let action = match item.action {
+                // there's no action menu to go back to
+                Action::RequestConfirmation(confirmation) => {
+                  Action::RequestConfirmation(Confirmation { cancel_mode: Mode::ServiceList, ..confirmation })
+                },
+                action => action,
+              };
+              MenuItem::new(&format!("{}: {}", unit.name, item.name), action)
+            }));
+          }
+          items
+            .extend(Command::PALETTE.iter().map(|&(command, name)| MenuItem::new(name, Action::RunCommand(command))));
+          items.extend([
+            MenuItem::new("Refresh the unit list", Action::RefreshServices),
+            MenuItem::new("Show/hide the debug logger", Action::ToggleShowLogger),
+            MenuItem::new("Help", Action::ToggleHelp),
+          ]);
+          self.palette_items = items;
+          self.palette_input.reset();
+          self.filter_palette();
+        } else if mode == Mode::ActionMenu {
+          let menu_items = self.unit_menu_items()?;
           self.menu_items = StatefulList::with_items(menu_items);
           self.menu_items.state.select(Some(0));
         }
 
+        if mode == Mode::EditNote {
+          let selected = self.filtered_units.selected()?;
+          let note = self.state.notes.get(&selected.name).cloned().unwrap_or_default();
+          self.note_input = Input::new(note);
+        }
+
         self.mode = mode;
         return Some(Action::Render);
       },
@@ -570,18 +1928,43 @@ impl Component for Home {
         self.error_message = err;
         return Some(Action::EnterMode(Mode::Error));
       },
-      Action::ToggleHelp => {
-        if self.mode != Mode::Help {
+      Action::SetStatusMessage(message) => {
+        self.status_message = Some((message, std::time::Instant::now()));
+        let tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+          tokio::time::sleep(STATUS_MESSAGE_DURATION).await;
+          let _ = tx.send(Action::ClearStatusMessage);
+        });
+        return Some(Action::Render);
+      },
+      // a newer message may have replaced the one this timer was for
+      Action::ClearStatusMessage
+        if self.status_message.as_ref().is_some_and(|(_, set_at)| set_at.elapsed() >= STATUS_MESSAGE_DURATION) =>
+      {
+        self.status_message = None;
+        return Some(Action::Render);
+      },
+      Action::RunCommand(command) => {
+        let tx = self.action_tx.clone().unwrap();
+        for action in self.run_command(command) {
+          let _ = tx.send(action);
+        }
+      },
+      Action::ToggleHelp => {
+        // go back to whatever was open before, e.g. the search box with its query. Toggling from within the help
+        // just closes it, Help itself is never remembered as the mode to go back to
+        if self.mode != Mode::Help {
           self.previous_mode = Some(self.mode);
           self.mode = Mode::Help;
+          self.help_scroll = 0;
         } else {
-          self.mode = self.previous_mode.unwrap_or(Mode::Search);
+          self.mode = self.previous_mode.take().unwrap_or(Mode::ServiceList);
         }
         return Some(Action::Render);
       },
       Action::CopyUnitFilePath => {
         if let Some(selected) = self.filtered_units.selected() {
-          if let Some(file_path) = &selected.file_path {
+          if let Some(file_path) = selected.file_path() {
             match clipboard_anywhere::set_clipboard(file_path) {
               Ok(_) => return Some(Action::EnterMode(Mode::ServiceList)),
               Err(e) => return Some(Action::EnterError { err: format!("Error copying to clipboard: {}", e) }),
@@ -591,23 +1974,520 @@ impl Component for Home {
           }
         }
       },
-      Action::SetUnitFilePath { unit, path } => {
+      Action::CopyFilterCommand => {
+        let command = self.filter_as_systemctl_command();
+        return match clipboard_anywhere::set_clipboard(&command) {
+          Ok(_) => Some(Action::SetStatusMessage(format!("Copied `{}`", command))),
+          Err(e) => Some(Action::EnterError { err: format!("Error copying to clipboard: {}", e) }),
+        };
+      },
+      Action::CopyLogs => {
+        if self.logs.is_empty() {
+          return Some(Action::SetStatusMessage("No logs to copy".into()));
+        }
+        // the logs as fetched, oldest first, without any of the styling they're rendered with
+        return match clipboard_anywhere::set_clipboard(&self.logs.join("\n")) {
+          Ok(_) => Some(Action::SetStatusMessage(format!("Copied {} log lines", self.logs.len()))),
+          Err(e) => Some(Action::EnterError { err: format!("Error copying to clipboard: {}", e) }),
+        };
+      },
+      Action::ExportLogs => {
+        let unit = self.selected_service()?;
+        if self.logs.is_empty() {
+          return Some(Action::SetStatusMessage("No logs to export".into()));
+        }
+        let file_name = format!("{}-{}.log", unit.name, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let path = std::env::current_dir().map(|dir| dir.join(&file_name)).unwrap_or_else(|_| file_name.into());
+        let mut contents = self.logs.join("\n");
+        contents.push('\n');
+        return match std::fs::write(&path, contents) {
+          Ok(_) => Some(Action::SetStatusMessage(format!("Wrote {} log lines to {}", self.logs.len(), path.display()))),
+          Err(e) => Some(Action::EnterError { err: format!("Error writing logs to {}: {}", path.display(), e) }),
+        };
+      },
+      Action::CopyErrorLogs => {
+        let selected = self.filtered_units.selected()?;
+        let properties = selected.properties.clone().unwrap_or_default();
+        let mut query = self.log_query(selected.id());
+        // short-iso output doesn't include the priority, so ask journalctl for just the warning+ entries
+        // since the oldest line we have (or fewer, if the logs are filtered by priority already)
+        query.priority = Some(query.priority.map_or(4, |p| p.min(4)));
+        let range = match self.logs.iter().find_map(|line| log_line_time(line)) {
+          Some(oldest) => format!("--since=@{}", oldest.timestamp()),
+          // no timestamps to go by, e.g. the lines from systemctl status. Look at as many lines as we have
+          None => format!("--lines={}", self.logs.len().max(1)),
+        };
+
+        let mut args = vec!["--quiet".to_string(), "--output=short-iso".into(), range];
+        args.extend(journalctl_filter_args(&query, &properties));
+        args.extend(self.config.journalctl_extra_args.iter().cloned());
+
+        let tx = self.action_tx.clone().unwrap();
+        tokio::task::spawn_blocking(move || {
+          let action = match cmd("journalctl", args).stderr_null().read() {
+            Ok(stdout) if stdout.trim().is_empty() => {
+              Action::SetStatusMessage("No error-level entries in current buffer".into())
+            },
+            Ok(stdout) => match clipboard_anywhere::set_clipboard(&stdout) {
+              Ok(_) => Action::SetStatusMessage(format!("Copied {} error/warning lines", stdout.lines().count())),
+              Err(e) => Action::EnterError { err: format!("Error copying to clipboard: {}", e) },
+            },
+            Err(e) => Action::EnterError { err: format!("Error getting error logs: {}", e) },
+          };
+          let _ = tx.send(action);
+        });
+        return Some(Action::EnterMode(Mode::ServiceList));
+      },
+      Action::SetUnitProperties { unit, properties } => {
+        // the filtered list has its own copy of the unit, update it in place rather than filtering everything again
+        let filtered = self.filtered_units.items.iter_mut().find(|u| u.name == unit.name && u.scope == unit.scope);
+        if let Some(item) = filtered {
+          item.properties = Some((*properties).clone());
+        }
         if let Some(unit) = self.all_units.get_mut(&unit) {
-          unit.file_path = Some(path.clone());
+          unit.properties = Some(*properties);
         }
-        self.refresh_filtered_units(); // copy the updated unit file path to the filtered list
+      },
+      Action::SaveNote => {
+        let selected = self.filtered_units.selected()?.name.clone();
+        let note = self.note_input.value().trim().to_string();
+        if note.is_empty() {
+          self.state.notes.remove(&selected);
+        } else {
+          self.state.notes.insert(selected, note);
+        }
+
+        if let Err(e) = self.state.save() {
+          return Some(Action::EnterError { err: format!("Failed to save note: {:#}", e) });
+        }
+        return Some(Action::EnterMode(Mode::ServiceList));
+      },
+      Action::ViewDropIns => {
+        let selected = self.filtered_units.selected()?;
+        let drop_in_paths = selected.properties.as_ref().map(|p| p.drop_in_paths.clone()).unwrap_or_default();
+        if drop_in_paths.is_empty() {
+          return Some(Action::EnterError { err: format!("No drop-in overrides found for {}", selected.name) });
+        }
+
+        self.drop_ins = StatefulList::with_items(drop_in_paths);
+        self.drop_ins.select(Some(0));
+        return Some(Action::EnterMode(Mode::DropIns));
+      },
+      Action::ViewUnitDiff => {
+        let selected = self.filtered_units.selected()?;
+        let properties = selected.properties.clone().unwrap_or_default();
+        let Some(fragment_path) = properties.fragment_path else {
+          return Some(Action::EnterError { err: format!("No unit file found for {}", selected.name) });
+        };
+
+        let unit = selected.id();
+        let tx = self.action_tx.clone().unwrap();
+        tokio::task::spawn_blocking(move || {
+          // a unit file in /etc replacing the vendor's is an override too, even without drop-ins
+          let vendor = match systemd::read_vendor_unit_file(&unit, &fragment_path) {
+            Some(vendor) => Ok(vendor),
+            None if properties.drop_in_paths.is_empty() => {
+              let _ = tx.send(Action::EnterError { err: format!("No overrides found for {}", unit.name) });
+              return;
+            },
+            None => systemd::read_file(&fragment_path),
+          };
+          let mut paths = properties.drop_in_paths;
+          paths.push(fragment_path);
+          let action = match (vendor, systemd::cat_unit(&unit)) {
+            (Ok(vendor), Ok(effective)) => {
+              let effective = strip_cat_headers(&effective, &paths);
+              Action::SetUnitDiff(unit_diff(&vendor, &effective))
+            },
+            (Err(e), _) | (_, Err(e)) => Action::EnterError { err: e.to_string() },
+          };
+          let _ = tx.send(action);
+        });
+      },
+      Action::SetUnitDiff(lines) => {
+        self.unit_diff = lines;
+        self.unit_diff_scroll = 0;
+        return Some(Action::EnterMode(Mode::UnitDiff));
+      },
+      Action::ViewUnitCat => {
+        let unit = self.filtered_units.selected()?.id();
+        let tx = self.action_tx.clone().unwrap();
+        tokio::task::spawn_blocking(move || {
+          let action = match systemd::cat_unit(&unit) {
+            Ok(output) => Action::SetUnitCat(output.lines().map(String::from).collect()),
+            Err(e) => Action::EnterError { err: e.to_string() },
+          };
+          let _ = tx.send(action);
+        });
+      },
+      Action::SetUnitCat(lines) => {
+        self.unit_cat = lines;
+        self.unit_cat_scroll = 0;
+        return Some(Action::EnterMode(Mode::UnitCat));
+      },
+      Action::EditUnitFile => {
+        let unit = self.filtered_units.selected()?.id();
+        let mut args = vec!["edit".to_string(), "--full".into(), unit.name];
+        if unit.scope == UnitScope::User {
+          args.insert(0, "--user".into());
+        }
+        if systemd::is_dry_run() {
+          let command = std::iter::once("systemctl").chain(args.iter().map(String::as_str)).join(" ");
+          return Some(Action::SetStatusMessage(format!("Dry run: would have run `{}`", command)));
+        }
+        // systemctl edit reloads the daemon itself, just show the changes once the editor is closed
+        return Some(Action::RunInTerminal {
+          program: "systemctl".into(),
+          args,
+          then: Some(Box::new(Action::RefreshServices)),
+        });
+      },
+      Action::ViewStatus => {
+        let unit = self.filtered_units.selected()?.id();
+        let tx = self.action_tx.clone().unwrap();
+        tokio::task::spawn_blocking(move || {
+          let action = match systemd::get_status(&unit) {
+            Ok(output) => Action::SetStatus(output.lines().map(String::from).collect()),
+            Err(e) => Action::EnterError { err: e.to_string() },
+          };
+          let _ = tx.send(action);
+        });
+      },
+      Action::SetStatus(lines) => {
+        self.status_lines = lines.iter().map(|l| ansi_line(l)).collect();
+        self.status_scroll = 0;
+        return Some(Action::EnterMode(Mode::Status));
+      },
+      Action::ViewDependencies => {
+        let unit = self.filtered_units.selected()?.id();
+        let tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+          let lookup_unit = unit.clone();
+          let dependencies =
+            match tokio::task::spawn_blocking(move || systemd::get_unit_dependencies(&lookup_unit)).await {
+              Ok(Ok(dependencies)) => dependencies,
+              Ok(Err(e)) => {
+                let _ = tx.send(Action::EnterError { err: e.to_string() });
+                return;
+              },
+              Err(e) => {
+                error!("Dependency lookup task failed: {}", e);
+                return;
+              },
+            };
+
+          // the dependencies are often other unit types, so look their states up separately from the main list
+          let names = dependencies.all_units();
+          let units = if names.is_empty() {
+            vec![]
+          } else {
+            systemd::get_services(unit.scope, UnitType::All, &names).await.unwrap_or_else(|e| {
+              warn!("Failed to get the states of {}'s dependencies: {}", unit.name, e);
+              vec![]
+            })
+          };
+          let _ = tx.send(Action::SetDependencies { unit, dependencies, units });
+        });
+      },
+      Action::SetDependencies { unit, dependencies, units } => {
+        self.dependency_states = units.into_iter().map(|u| (u.name, u.active_state)).collect();
+        let rows = dependencies
+          .kinds()
+          .into_iter()
+          .flat_map(|(kind, names)| names.iter().map(move |name| (kind, name.clone())))
+          .collect_vec();
+        self.dependencies = StatefulList::with_items(rows);
+        if !self.dependencies.items.is_empty() {
+          self.dependencies.select(Some(0));
+        }
+        self.dependencies_of = Some(unit);
+        return Some(Action::EnterMode(Mode::Dependencies));
+      },
+      Action::GoToUnit(unit) => {
+        if !self.all_units.contains_key(&unit) {
+          // not in the list, e.g. a different unit type. Look it up and add it
+          let tx = self.action_tx.clone().unwrap();
+          tokio::spawn(async move {
+            let names = std::slice::from_ref(&unit.name);
+            let action = match systemd::get_services(unit.scope, UnitType::All, names).await {
+              Ok(units) if !units.is_empty() => Action::AddUnits { units, go_to: unit },
+              Ok(_) => Action::SetStatusMessage(format!("{} isn't loaded", unit.name)),
+              Err(e) => Action::EnterError { err: format!("Failed to get {}: {}", unit.name, e) },
+            };
+            let _ = tx.send(action);
+          });
+          return None;
+        }
+
+        if !self.select_unit(&unit) {
+          // hidden by the search or a filter, clear them to reveal it
+          self.input.reset();
+          self.invert_filter = false;
+          self.show_recent_errors_only = false;
+          self.show_failed_only = false;
+          self.refresh_filtered_units();
+          self.select_unit(&unit);
+        }
+        return Some(Action::EnterMode(Mode::ServiceList));
+      },
+      Action::AddUnits { units, go_to } => {
+        for unit in units {
+          self.all_units.insert(unit.id(), unit);
+        }
+        self.refresh_filtered_units();
+        return Some(Action::GoToUnit(go_to));
+      },
+      Action::OpenEnvironmentShell => {
+        let selected = self.filtered_units.selected()?;
+        let environment = selected.properties.as_ref().map(|p| p.environment.clone()).unwrap_or_default();
+        if environment.is_empty() {
+          return Some(Action::EnterError { err: format!("No Environment= variables set for {}", selected.name) });
+        }
+
+        // `env` sets the variables for the shell, so RunInTerminal doesn't need to know about environments
+        let shell = std::env::var("SHELL").ok().filter(|s| !s.is_empty()).unwrap_or_else(|| "sh".into());
+        let mut args = environment.iter().map(|(key, value)| format!("{key}={value}")).collect_vec();
+        args.push(shell.clone());
+
+        let confirmation = Confirmation {
+          prompt: format!(
+            "Open {} with {}'s environment?\n\
+             Values may contain secrets (passwords, tokens) and will be visible to the shell.",
+            shell, selected.name
+          ),
+          items: environment.into_iter().map(|(key, _)| key).collect(),
+          action: Box::new(Action::RunInTerminal { program: "env".into(), args, then: None }),
+          cancel_mode: Mode::ActionMenu,
+        };
+        return Some(Action::RequestConfirmation(confirmation));
+      },
+      Action::ToggleInvocationLogs => {
+        self.logs_current_invocation_only = !self.logs_current_invocation_only;
+        self.reload_logs();
+        return Some(Action::EnterMode(Mode::ServiceList));
+      },
+      Action::ToggleRecentErrorsFilter => {
+        let cache_is_fresh = self
+          .units_with_recent_errors
+          .as_ref()
+          .is_some_and(|(checked_at, _)| checked_at.elapsed() < RECENT_ERRORS_CACHE_DURATION);
+
+        if !self.show_recent_errors_only && !cache_is_fresh {
+          self.find_units_with_recent_errors();
+          return None;
+        }
+
+        self.show_recent_errors_only = !self.show_recent_errors_only;
+        self.refresh_filtered_units();
+        return Some(Action::Render);
+      },
+      Action::ToggleFailedOnly => {
+        self.show_failed_only = !self.show_failed_only;
+        self.refresh_filtered_units();
+        return Some(Action::Render);
+      },
+      Action::SetUnitsWithRecentErrors(units) => {
+        self.units_with_recent_errors = Some((std::time::Instant::now(), units));
+        self.show_recent_errors_only = true;
+        self.refresh_filtered_units();
+        return Some(Action::EnterMode(Mode::ServiceList));
+      },
+      Action::ToggleDetailsExpanded => {
+        self.details_expanded = !self.details_expanded;
+        return Some(Action::Render);
+      },
+      Action::ToggleFollowLogs => {
+        self.logs_snapshot_only = !self.logs_snapshot_only;
+        self.reload_logs();
+        return Some(Action::Render);
+      },
+      Action::ToggleSplit => {
+        self.split = self.split.toggle();
+        self.state.split = Some(self.split);
+        if let Err(e) = self.state.save() {
+          return Some(Action::EnterError { err: format!("Failed to save the layout: {:#}", e) });
+        }
+        return Some(Action::Render);
+      },
+      Action::ToggleLogWrap => {
+        self.log_wrap = !self.log_wrap;
+        self.logs_horizontal_offset = 0;
+        // the rows the lines take up changed, so the old offset points somewhere else
+        self.logs_scroll_offset = 0;
+        return Some(Action::Render);
+      },
+      Action::TogglePauseLogs => {
+        let unit = self.selected_service()?;
+        match self.logs_paused.take() {
+          Some((paused_unit, buffered)) if paused_unit == unit => self.logs.extend(buffered),
+          _ => self.logs_paused = Some((unit, VecDeque::new())),
+        }
+        return Some(Action::Render);
+      },
+      Action::ToggleChildLogs => {
+        self.logs_include_children = !self.logs_include_children;
+        self.reload_logs();
+        return Some(Action::Render);
+      },
+      Action::ToggleLogsFocus => {
+        self.logs_focused = !self.logs_focused;
+        return Some(Action::Render);
+      },
+      Action::ReloadLogs => {
+        self.reload_logs();
+        return Some(Action::Render);
+      },
+      Action::LoadMoreLogs => {
+        self.log_lines = self.log_lines.saturating_mul(2).max(1);
+        self.reload_logs();
+        return Some(Action::SetStatusMessage(format!("Loading the last {} log lines", self.log_lines)));
+      },
+      Action::ToggleCurrentBootLogs => {
+        self.logs_boot = if self.logs_boot == Some(0) { None } else { Some(0) };
+        self.reload_logs();
+        return Some(Action::Render);
+      },
+      Action::CycleLogPriority => {
+        // all → emerg → alert → ... → debug → all
+        self.logs_priority = match self.logs_priority {
+          None => Some(0),
+          Some(p) if (p as usize) < LOG_PRIORITIES.len() - 1 => Some(p + 1),
+          Some(_) => None,
+        };
+        self.reload_logs();
+        return Some(Action::Render);
+      },
+      Action::ViewUnitFiles => {
+        let tx = self.action_tx.clone().unwrap();
+        let scopes = match self.scope {
+          Scope::Global => vec![UnitScope::Global],
+          Scope::User => vec![UnitScope::User],
+          Scope::All => vec![UnitScope::Global, UnitScope::User],
+        };
+        let unit_type = self.unit_type;
+        // like listing units, the user scope may not be reachable when showing all of them
+        let user_scope_optional = matches!(self.scope, Scope::All);
+        tokio::spawn(async move {
+          let mut unit_files = vec![];
+          for scope in scopes {
+            match systemd::get_unit_files(scope, unit_type).await {
+              Ok(files) => unit_files.extend(files),
+              Err(e) => match systemd::user_scope_unavailable_reason() {
+                Some(reason) if scope == UnitScope::User && user_scope_optional => {
+                  error!("Failed to list user unit files, ignoring because {}: {}", reason, e);
+                },
+                _ => {
+                  let _ = tx.send(Action::EnterError { err: format!("Failed to list unit files: {}", e) });
+                  return;
+                },
+              },
+            }
+          }
+          let _ = tx.send(Action::SetUnitFiles(unit_files));
+        });
+      },
+      Action::SetUnitFiles(unit_files) => {
+        self.unit_files = StatefulList::with_items(unit_files);
+        self.unit_files.select(Some(0));
+        self.marked_unit_files.clear();
+        return Some(Action::EnterMode(Mode::UnitFiles));
+      },
+      Action::BatchSetEnabled { units, enable } => {
+        let tx = self.action_tx.clone().unwrap();
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+        self.marked_unit_files.clear();
+        let spinner_task = spawn_spinner(tx.clone());
+
+        tokio::spawn(async move {
+          tx.send(Action::EnterMode(Mode::Processing)).unwrap();
+          let mut failures = vec![];
+          for unit in &units {
+            let result = if enable {
+              systemd::enable_service(unit.clone(), cancel_token.clone()).await
+            } else {
+              systemd::disable_service(unit.clone(), cancel_token.clone()).await
+            };
+            if cancel_token.is_cancelled() {
+              warn!("Batch enable/disable was cancelled at {}", unit.name);
+              break;
+            }
+            if let Err(e) = result {
+              error!("Failed to set enabled={} for {:?} unit {}: {}", enable, unit.scope, unit.name, e);
+              failures.push(format!("{}: {}", unit.name, e));
+            }
+          }
+          spinner_task.abort();
+
+          let verb = if enable { "Enabled" } else { "Disabled" };
+          let succeeded = units.len() - failures.len();
+          if failures.is_empty() {
+            tx.send(Action::EnterMode(Mode::ServiceList)).unwrap();
+            tx.send(Action::SetStatusMessage(format!("{} {} unit(s)", verb, succeeded))).unwrap();
+          } else {
+            let err = format!("{} {} of {} unit(s), failed:\n\n{}", verb, succeeded, units.len(), failures.join("\n"));
+            tx.send(Action::EnterError { err }).unwrap();
+          }
+          tx.send(Action::RefreshServices).unwrap();
+        });
+      },
+      Action::ViewBoots => {
+        let tx = self.action_tx.clone().unwrap();
+        tokio::task::spawn_blocking(move || {
+          let action = match systemd::list_boots() {
+            Ok(boots) => Action::SetBoots(boots),
+            Err(e) => Action::EnterError { err: format!("Failed to list boots: {}", e) },
+          };
+          let _ = tx.send(action);
+        });
+      },
+      Action::SetBoots(boots) => {
+        // newest first, after the "all boots" entry
+        let mut items = vec![None];
+        items.extend(boots.into_iter().rev().map(Some));
+        let selected = items.iter().position(|b| b.as_ref().map(|b| b.offset) == self.logs_boot).unwrap_or(0);
+        self.boots = StatefulList::with_items(items);
+        self.boots.select(Some(selected));
+        return Some(Action::EnterMode(Mode::Boots));
+      },
+      Action::SetLogsBoot(boot) => {
+        self.logs_boot = boot;
+        self.reload_logs();
+        return Some(Action::EnterMode(Mode::ServiceList));
       },
       Action::SetLogs { unit, logs } => {
         if let Some(selected) = self.filtered_units.selected() {
           if selected.id() == unit {
+            if let Some(bookmarks) = self.log_bookmarks.get_mut(&unit) {
+              remap_log_bookmarks(bookmarks, &self.logs, &logs);
+            }
             self.logs = logs;
+            self.logs_from_status = false;
+          }
+        }
+      },
+      Action::SetStatusLogs { unit, logs } => {
+        if let Some(selected) = self.filtered_units.selected() {
+          if selected.id() == unit {
+            self.logs = logs;
+            self.logs_from_status = true;
           }
         }
       },
       Action::AppendLogLine { unit, line } => {
         if let Some(selected) = self.filtered_units.selected() {
           if selected.id() == unit {
-            self.logs.push(line);
+            match &mut self.logs_paused {
+              Some((paused_unit, buffered)) if *paused_unit == unit => {
+                if self.config.paused_logs == PausedLogs::Buffer {
+                  buffered.push_back(line);
+                  if buffered.len() > MAX_PAUSED_LOG_LINES {
+                    buffered.pop_front();
+                  }
+                }
+              },
+              _ => self.logs.push(line),
+            }
           }
         }
       },
@@ -619,38 +2499,403 @@ impl Component for Home {
         self.logs_scroll_offset = self.logs_scroll_offset.saturating_add(offset);
         info!("scroll offset: {}", self.logs_scroll_offset);
       },
+      Action::ScrollLeft(offset) => {
+        self.logs_horizontal_offset = self.logs_horizontal_offset.saturating_sub(offset);
+      },
+      Action::ScrollRight(offset) if !self.log_wrap => {
+        self.logs_horizontal_offset = self.logs_horizontal_offset.saturating_add(offset);
+      },
+      Action::ToggleLogBookmark => {
+        let unit = self.selected_service()?;
+        let index = self.log_line_at_scroll_offset()?;
+
+        let bookmarks = self.log_bookmarks.entry(unit).or_default();
+        let message = if let Some(position) = bookmarks.iter().position(|&b| b == index) {
+          bookmarks.remove(position);
+          "Removed bookmark".to_string()
+        } else {
+          bookmarks.push(index);
+          format!("Added bookmark {}", bookmarks.len())
+        };
+        return Some(Action::SetStatusMessage(message));
+      },
+      Action::NextLogMatch | Action::PreviousLogMatch => {
+        if self.log_search.value().is_empty() {
+          return Some(Action::SetStatusMessage("Focus the logs with Tab, then press / to search them".into()));
+        }
+        if !self.jump_to_log_match(matches!(action, Action::NextLogMatch), false) {
+          return Some(Action::SetStatusMessage(format!("No log lines match `{}`", self.log_search.value())));
+        }
+        return Some(Action::Render);
+      },
+      Action::NextLogBookmark => {
+        let unit = self.selected_service()?;
+        let bookmarks = self.log_bookmarks.get(&unit).map(Vec::as_slice).unwrap_or_default();
+        let line_offsets = self.log_line_offsets();
+        let mut offsets = bookmarks.iter().filter_map(|&i| line_offsets.get(i).copied()).sorted().peekable();
+        if offsets.peek().is_none() {
+          return Some(Action::SetStatusMessage("No bookmarks in the current logs".into()));
+        }
+
+        // the next bookmark below the current position, wrapping around to the first one
+        let first = *offsets.peek().unwrap();
+        self.logs_scroll_offset = offsets.find(|&o| o > self.logs_scroll_offset).unwrap_or(first);
+        return Some(Action::Render);
+      },
       Action::ScrollToTop => {
         self.logs_scroll_offset = 0;
       },
+      Action::MouseClick { column, row } => match self.mode {
+        Mode::ActionMenu => {
+          let index = list_row_at(self.action_menu_panel, column, row)? + self.menu_items.state.offset();
+          let action = self.menu_items.items.get(index)?.action.clone();
+          self.menu_items.select(Some(index));
+          return Some(action);
+        },
+        Mode::ServiceList | Mode::Search => {
+          let index = list_row_at(self.services_panel, column, row)? + self.filtered_units.state.offset();
+          if index < self.filtered_units.items.len() {
+            self.select(Some(index));
+            return Some(Action::Render);
+          }
+        },
+        _ => {},
+      },
+      Action::MouseScroll { column, row, down } => {
+        if self.mode != Mode::ServiceList {
+          return None;
+        }
+        if list_row_at(self.logs_panel, column, row).is_some() {
+          return Some(if down { Action::ScrollDown(3) } else { Action::ScrollUp(3) });
+        }
+        if list_row_at(self.services_panel, column, row).is_some() {
+          if down {
+            self.next();
+          } else {
+            self.previous();
+          }
+          return Some(Action::Render);
+        }
+      },
       Action::ScrollToBottom => {
-        // TODO: this is partially broken, figure out a better way to scroll to end
-        // problem: we don't actually know the height of the paragraph before it's rendered
-        // because it's wrapped based on the width of the widget
-        // A proper fix might need to wait until ratatui improves scrolling: https://github.com/ratatui-org/ratatui/issues/174
-        self.logs_scroll_offset = self.logs.len() as u16;
+        let rows: usize = self.logs.iter().map(|l| self.log_line_rows(l)).sum();
+        let offset = rows.saturating_sub(self.logs_viewport_height as usize);
+        self.logs_scroll_offset = offset.min(u16::MAX as usize) as u16;
       },
 
       Action::StartService(service_name) => self.start_service(service_name),
       Action::StopService(service_name) => self.stop_service(service_name),
+      Action::ViewKillSignals(unit) => {
+        // swap the menu's contents, staying in (or, from the command palette, going to) the action menu
+        self.mode = Mode::ActionMenu;
+        let menu_items = Signal::ALL
+          .into_iter()
+          .map(|signal| {
+            let action = Action::KillService { unit: unit.clone(), signal };
+            MenuItem::new(signal.name(), self.confirm_destructive(&format!("Send {} to", signal.name()), action))
+          })
+          .collect();
+        self.menu_items = StatefulList::with_items(menu_items);
+        self.menu_items.state.select(Some(0));
+        return Some(Action::Render);
+      },
+      Action::KillService { unit, signal } => self.kill_service(unit, signal),
       Action::RestartService(service_name) => self.restart_service(service_name),
+      Action::ReloadService(service_name) => self.reload_service(service_name),
+      Action::EnableService(service_name) => self.enable_service(service_name),
+      Action::DisableService(service_name) => self.disable_service(service_name),
+      Action::MaskService(service_name) => self.mask_service(service_name),
+      Action::UnmaskService(service_name) => self.unmask_service(service_name),
+      Action::RestartWithDependencies { unit, reverse } => {
+        let tx = self.action_tx.clone().unwrap();
+        tokio::task::spawn_blocking(move || match systemd::get_dependencies(&unit, reverse) {
+          Ok(dependencies) => {
+            let mut units = dependencies.into_iter().map(|name| UnitId { name, scope: unit.scope }).collect_vec();
+            // dependencies should be up before the unit itself, dependents after it
+            if reverse {
+              units.insert(0, unit.clone());
+            } else {
+              units.push(unit.clone());
+            }
+
+            let relation = if reverse { "dependents" } else { "dependencies" };
+            let confirmation = Confirmation {
+              prompt: format!("Restart {} and its {}?", unit.name, relation),
+              items: units.iter().map(|u| u.name.clone()).collect(),
+              action: Box::new(Action::RestartServices(units)),
+              cancel_mode: Mode::ActionMenu,
+            };
+            let _ = tx.send(Action::RequestConfirmation(confirmation));
+          },
+          Err(e) => {
+            let _ = tx.send(Action::EnterError { err: format!("Failed to list dependencies of {}: {}", unit.name, e) });
+          },
+        });
+        return Some(Action::EnterMode(Mode::ServiceList));
+      },
+      Action::DaemonReload => {
+        let tx = self.action_tx.clone().unwrap();
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+        let scopes = match self.scope {
+          Scope::Global => vec![UnitScope::Global],
+          Scope::User => vec![UnitScope::User],
+          Scope::All => vec![UnitScope::Global, UnitScope::User],
+        };
+        // like listing units, the user scope may not be reachable when showing all of them
+        let user_scope_optional = matches!(self.scope, Scope::All);
+        let spinner_task = spawn_spinner(tx.clone());
+
+        tokio::spawn(async move {
+          tx.send(Action::EnterMode(Mode::Processing)).unwrap();
+          let mut result = Ok(());
+          for &scope in &scopes {
+            result = systemd::daemon_reload(scope, cancel_token.clone()).await;
+            match &result {
+              Ok(_) => {},
+              Err(SystemdError::Cancelled) => break,
+              Err(e) => match systemd::user_scope_unavailable_reason() {
+                Some(reason) if scope == UnitScope::User && user_scope_optional => {
+                  error!("User daemon-reload failed, ignoring because {}: {}", reason, e);
+                  result = Ok(());
+                },
+                _ => break,
+              },
+            }
+          }
+          spinner_task.abort();
+
+          match result {
+            Ok(_) => {
+              tx.send(Action::EnterMode(Mode::ServiceList)).unwrap();
+              let message = if systemd::is_dry_run() {
+                let commands = scopes.into_iter().map(systemd::daemon_reload_command).join("`, `");
+                format!("Dry run: would have run `{}`", commands)
+              } else {
+                "Reloaded systemd configuration".into()
+              };
+              tx.send(Action::SetStatusMessage(message)).unwrap();
+            },
+            Err(SystemdError::Cancelled) => warn!("daemon-reload was cancelled"),
+            Err(e) => {
+              error!("daemon-reload failed: {}", e);
+              tx.send(Action::EnterError { err: format!("daemon-reload failed: {}", e) }).unwrap();
+            },
+          }
+          tx.send(Action::RefreshServices).unwrap();
+        });
+      },
+      Action::RestartServices(units) => {
+        let tx = self.action_tx.clone().unwrap();
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+        for unit in &units {
+          self.log_cache.shift_remove(unit);
+        }
+        let spinner_task = spawn_spinner(tx.clone());
+
+        tokio::spawn(async move {
+          tx.send(Action::EnterMode(Mode::Processing)).unwrap();
+          let (mut restarted, mut failure) = (vec![], None);
+          // in order, stopping at the first failure: the rest of the stack likely depends on that unit
+          for (i, unit) in units.iter().enumerate() {
+            tx.send(Action::SetProcessingStatus(format!("Restarting {}/{}", i + 1, units.len()))).unwrap();
+            let result = systemd::restart_service(unit.clone(), cancel_token.clone()).await;
+            if cancel_token.is_cancelled() {
+              warn!("Restarting with dependencies was cancelled at {}", unit.name);
+              break;
+            }
+            if !systemd::is_dry_run() {
+              let last_action =
+                LastAction { name: "Restart".into(), time: chrono::Local::now(), succeeded: result.is_ok() };
+              tx.send(Action::SetLastAction { unit: unit.clone(), action: last_action }).unwrap();
+            }
+            match result {
+              Ok(_) => restarted.push(unit.name.clone()),
+              Err(e) => {
+                error!("Failed to restart {:?} unit {}: {}", unit.scope, unit.name, e);
+                failure = Some((unit.name.clone(), e));
+                break;
+              },
+            }
+          }
+          spinner_task.abort();
+
+          let summary = format!("Restarted {} of {} unit(s)", restarted.len(), units.len());
+          if cancel_token.is_cancelled() {
+            tx.send(Action::SetStatusMessage(format!("Cancelled. {summary}"))).unwrap();
+          } else if let Some((name, e)) = failure {
+            let mut err = format!("Failed to restart {name}: {e}\n\n{summary}");
+            if !restarted.is_empty() {
+              err.push_str(&format!("\n\nRestarted:\n{}", restarted.join("\n")));
+            }
+            tx.send(Action::EnterError { err }).unwrap();
+          } else {
+            tx.send(Action::EnterMode(Mode::ServiceList)).unwrap();
+            tx.send(Action::SetStatusMessage(summary)).unwrap();
+          }
+          tx.send(Action::RefreshServices).unwrap();
+        });
+      },
+      Action::RequestConfirmation(confirmation) => {
+        self.confirmation = Some(confirmation);
+        return Some(Action::EnterMode(Mode::Confirm));
+      },
+      Action::Confirm => {
+        let confirmation = self.confirmation.take()?;
+        self.mode = Mode::ServiceList;
+        return Some(*confirmation.action);
+      },
       Action::RefreshServices => {
+        if self.systemd_missing {
+          return None;
+        }
         let tx = self.action_tx.clone().unwrap();
         let scope = self.scope;
+        let unit_type = self.unit_type;
+        let limit_units = self.limit_units.clone();
         tokio::spawn(async move {
-          let units = systemd::get_all_services(scope)
-            .await
-            .expect("Failed to get services. Check that systemd is running and try running this tool with sudo.");
-          tx.send(Action::SetServices(units)).unwrap();
+          // this runs periodically, a failed refresh shouldn't take the app down; the next one may well work
+          match systemd::get_all_services(scope, unit_type, &limit_units).await {
+            Ok(units) => tx.send(Action::SetServices(units)).unwrap(),
+            Err(e) => warn!("Failed to refresh services: {}", e),
+          }
         });
       },
       Action::SetServices(units) => {
         self.update_units(units);
+        if self.show_age {
+          self.fetch_state_change_timestamps();
+        }
+        return Some(Action::Render);
+      },
+      Action::ToggleAgeColumn => {
+        self.show_age = !self.show_age;
+        if self.show_age {
+          self.fetch_state_change_timestamps();
+        }
+        return Some(Action::Render);
+      },
+      Action::SetLastAction { unit, action } => {
+        self.last_actions.insert(unit, action);
+        return Some(Action::Render);
+      },
+      Action::ToggleGroupInstances => {
+        self.group_instances = !self.group_instances;
+        if !self.group_instances {
+          self.instance_groups.clear();
+        }
+        self.refresh_filtered_units();
+        return Some(Action::Render);
+      },
+      Action::ToggleFullNames => {
+        self.show_full_names = !self.show_full_names;
+        // the search matches what's shown
+        self.refresh_filtered_units();
+        return Some(Action::Render);
+      },
+      Action::SetStateChangeTimestamps(timestamps) => {
+        for (unit, timestamp) in timestamps {
+          if let Some(u) = self.all_units.get_mut(&unit) {
+            u.state_change_timestamp = Some(timestamp);
+          }
+        }
+        self.refresh_filtered_units();
+        return Some(Action::Render);
+      },
+      Action::ToggleInvertFilter => {
+        self.invert_filter = !self.invert_filter;
+        self.refresh_filtered_units();
+        return Some(Action::Render);
+      },
+      Action::CycleUnitType => {
+        self.unit_type = self.unit_type.next();
+        return Some(Action::Reinitialize);
+      },
+      Action::CycleScope => {
+        self.scope = self.scope.next();
+        return Some(Action::Reinitialize);
+      },
+      Action::Reinitialize => {
+        info!("Reinitializing: restarting log dispatcher and re-enumerating services");
+        self.spawn_log_dispatcher();
+
+        let tx = self.action_tx.clone().unwrap();
+        let scope = self.scope;
+        let unit_type = self.unit_type;
+        let limit_units = self.limit_units.clone();
+        tokio::spawn(async move {
+          match systemd::get_all_services(scope, unit_type, &limit_units).await {
+            Ok(units) => tx.send(Action::ResetServices(units)).unwrap(),
+            Err(e) => tx.send(Action::EnterError { err: format!("Failed to reinitialize: {:#}", e) }).unwrap(),
+          }
+        });
+      },
+      Action::ResetServices(units) => {
+        self.set_units(units);
+        // the new dispatcher isn't following anything yet
+        self.reload_logs();
         return Some(Action::Render);
       },
       Action::SpinnerTick => {
         self.spinner_tick = self.spinner_tick.wrapping_add(1);
         return Some(Action::Render);
       },
+      Action::BatchServiceAction { units, action } => {
+        let tx = self.action_tx.clone().unwrap();
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+        self.marked_units.clear();
+        for unit in &units {
+          self.log_cache.shift_remove(unit);
+        }
+        let spinner_task = spawn_spinner(tx.clone());
+
+        tokio::spawn(async move {
+          tx.send(Action::EnterMode(Mode::Processing)).unwrap();
+          let (mut succeeded, mut failures) = (vec![], vec![]);
+          // one at a time, like systemctl does when given several units
+          for (i, unit) in units.iter().enumerate() {
+            tx.send(Action::SetProcessingStatus(format!("{} {}/{}", action.progress(), i + 1, units.len()))).unwrap();
+            let result = match action {
+              BatchAction::Start => systemd::start_service(unit.clone(), cancel_token.clone()).await,
+              BatchAction::Stop => systemd::stop_service(unit.clone(), cancel_token.clone()).await,
+              BatchAction::Restart => systemd::restart_service(unit.clone(), cancel_token.clone()).await,
+            };
+            if cancel_token.is_cancelled() {
+              warn!("Batch {} was cancelled at {}", action.verb().to_lowercase(), unit.name);
+              break;
+            }
+            match result {
+              Ok(_) => succeeded.push(unit.name.clone()),
+              Err(e) => {
+                error!("Failed to {} {:?} unit {}: {}", action.verb().to_lowercase(), unit.scope, unit.name, e);
+                failures.push(format!("{}: {}", unit.name, e));
+              },
+            }
+          }
+          spinner_task.abort();
+
+          let summary = format!("{} {} of {} unit(s)", action.past(), succeeded.len(), units.len());
+          if cancel_token.is_cancelled() {
+            tx.send(Action::SetStatusMessage(format!("Cancelled. {summary}"))).unwrap();
+          } else if failures.is_empty() {
+            tx.send(Action::EnterMode(Mode::ServiceList)).unwrap();
+            tx.send(Action::SetStatusMessage(summary)).unwrap();
+          } else {
+            let mut err = format!("{summary}\n\nFailed:\n{}", failures.join("\n"));
+            if !succeeded.is_empty() {
+              err.push_str(&format!("\n\nSucceeded:\n{}", succeeded.join("\n")));
+            }
+            tx.send(Action::EnterError { err }).unwrap();
+          }
+          tx.send(Action::RefreshServices).unwrap();
+        });
+      },
+      Action::SetProcessingStatus(status) => {
+        self.processing_status = Some(status);
+        return Some(Action::Render);
+      },
       Action::CancelTask => {
         if let Some(cancel_token) = self.cancel_token.take() {
           cancel_token.cancel();
@@ -673,11 +2918,51 @@ impl Component for Home {
       rect
     };
 
-    let rects = Layout::new(Direction::Vertical, [Constraint::Min(3), Constraint::Percentage(100)]).split(rect);
+    let theme = self.theme;
+    let rects =
+      Layout::new(Direction::Vertical, [Constraint::Min(3), Constraint::Percentage(100), Constraint::Length(1)])
+        .split(rect);
     let search_panel = rects[0];
     let main_panel = rects[1];
+    let status_bar = rects[2];
+
+    let mut status_spans = vec![];
+    if systemd::is_dry_run() {
+      status_spans.push(Span::styled(" DRY RUN ", Style::default().fg(theme.marker_text).bg(theme.marker)));
+      status_spans.push(Span::raw(" "));
+    }
+    if let Some((message, _)) = &self.status_message {
+      status_spans.push(Span::raw(message.as_str()));
+    }
+    f.render_widget(Paragraph::new(Line::from(status_spans)), status_bar);
 
-    fn colored_line(value: &str, color: Color) -> Line {
+    // unit counts on the right, colored like the Active row in the details pane
+    let (active, failed) = self
+      .all_units
+      .values()
+      .fold((0, 0), |(active, failed), u| (active + u.is_active() as usize, failed + u.is_failed() as usize));
+    let inactive = self.all_units.len() - active - failed;
+    let separator = || Span::styled(" · ", Style::default().fg(theme.dim));
+    let mut count_spans = vec![
+      Span::styled(format!("{active} active"), Style::default().fg(theme.state_active)),
+      separator(),
+      Span::styled(format!("{inactive} inactive"), Style::default().fg(theme.state_inactive)),
+      separator(),
+      Span::styled(
+        format!("{failed} failed"),
+        Style::default().fg(if failed > 0 { theme.state_failed } else { theme.muted }),
+      ),
+      separator(),
+      Span::raw(format!("{} total ", self.all_units.len())),
+    ];
+    if !self.input.value().is_empty() || self.show_recent_errors_only || self.show_failed_only {
+      count_spans
+        .insert(0, Span::raw(format!("showing {} of {} · ", self.filtered_units.items.len(), self.all_units.len())));
+    }
+    let counts = Paragraph::new(Line::from(count_spans)).alignment(ratatui::layout::Alignment::Right);
+    f.render_widget(counts, status_bar);
+
+    fn colored_line(value: &str, color: Color) -> Line<'_> {
       Line::from(vec![Span::styled(value, Style::default().fg(color))])
     }
 
@@ -687,26 +2972,104 @@ impl Component for Home {
     //    green       active
     //    red         failed
     //    yellow      not-found
-    fn unit_color(unit: &UnitWithStatus) -> Color {
-      if unit.is_active() {
-        Color::Green
-      } else if unit.is_failed() {
-        Color::Red
-      } else if unit.is_not_found() {
-        Color::Yellow
+    fn unit_color(unit: &UnitWithStatus, theme: &Theme) -> Color {
+      match active_state_color(&unit.active_state, theme) {
+        Some(color) if unit.active_state != "inactive" => color,
+        _ if unit.is_not_found() => theme.state_warning,
+        _ => theme.text,
+      }
+    }
+
+    let (list_panel, right_panel) = if self.split == Split::Stacked {
+      let chunks = Layout::new(Direction::Vertical, Constraint::from_percentages([40, 60])).split(main_panel);
+      (chunks[0], chunks[1])
+    } else if main_panel.width < NARROW_LAYOUT_WIDTH {
+      // not enough room for both side by side, show one pane at a time (Tab switches)
+      if self.logs_focused {
+        (Rect::default(), main_panel)
       } else {
-        Color::White
+        (main_panel, Rect::default())
+      }
+    } else {
+      let chunks =
+        Layout::new(Direction::Horizontal, [Constraint::Min(30), Constraint::Percentage(100)]).split(main_panel);
+      (chunks[0], chunks[1])
+    };
+
+    // Only build items for the rows that fit in the panel, there can be thousands of units.
+    // Keep the selection in view the same way List would, but track the offset ourselves
+    let viewport_height = list_panel.height.saturating_sub(2) as usize;
+    let unit_count = self.filtered_units.items.len();
+    let selected = self.filtered_units.state.selected();
+    let mut offset = self.filtered_units.state.offset().min(unit_count.saturating_sub(1));
+    if let Some(selected) = selected {
+      if selected < offset {
+        offset = selected;
+      } else if viewport_height > 0 && selected >= offset + viewport_height {
+        offset = selected + 1 - viewport_height;
       }
     }
+    *self.filtered_units.state.offset_mut() = offset;
+    let visible_units = &self.filtered_units.items[offset..(offset + viewport_height).min(unit_count)];
+    let mut visible_state = ListState::default().with_selected(selected.map(|s| s - offset));
 
-    let items: Vec<ListItem> = self
-      .filtered_units
-      .items
+    // pad names so the age column lines up
+    let name_width =
+      visible_units.iter().map(|i| self.group_prefix(i).chars().count() + self.list_name(i).chars().count()).max();
+    let name_width = name_width.unwrap_or(0);
+    let now = chrono::Utc::now().timestamp();
+    // which characters of each name the search matched, nothing to show for an empty or inverted search
+    let search = self.input.value();
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let highlight_search = !search.is_empty() && !self.invert_filter;
+
+    let visible_selected = visible_state.selected();
+    let items: Vec<ListItem> = visible_units
       .iter()
-      .map(|i| {
-        let color = unit_color(i);
-        let line = colored_line(i.short_name(), color);
-        ListItem::new(line)
+      .enumerate()
+      .map(|(row, i)| {
+        // the selected row's background is the highlight color, anything drawn in that color would vanish on it
+        let fg = |color: Color| {
+          let color = if visible_selected == Some(row) && color == theme.highlight { theme.muted } else { color };
+          Style::default().fg(color)
+        };
+        let color = unit_color(i, &theme);
+        let indicator = self.health_style.indicator(i.health());
+        let mut spans = vec![];
+        if !self.marked_units.is_empty() {
+          let mark = match (self.marked_units.contains(&i.id()), self.config.ascii) {
+            (true, false) => "✓ ",
+            (true, true) => "+ ",
+            (false, _) => "  ",
+          };
+          spans.push(Span::styled(mark, fg(theme.marker)));
+        }
+        spans.extend([Span::styled(indicator.glyph.as_str(), fg(indicator.color)), Span::raw(" ")]);
+        let prefix = self.group_prefix(i);
+        spans.push(Span::styled(prefix, fg(theme.dim)));
+        let name = self.list_name(i);
+        let matched = if highlight_search { matcher.fuzzy_indices(name, search) } else { None };
+        match matched {
+          Some((_, indices)) => spans.extend(highlight_chars(name, &indices, fg(color))),
+          None => spans.push(Span::styled(name, fg(color))),
+        }
+        if self.show_age {
+          let age = i.state_change_timestamp.map(|t| format_age(now - t)).unwrap_or_default();
+          let padding = name_width - prefix.chars().count() - name.chars().count();
+          spans.push(Span::styled(format!("{} {:>4}", " ".repeat(padding), age), fg(theme.dim)));
+        }
+        // with both scopes merged, tag each row so system and user units with the same name can be told apart
+        if matches!(self.scope, Scope::All) {
+          let tag = match i.scope {
+            UnitScope::Global => " system",
+            UnitScope::User => " user",
+          };
+          spans.push(Span::styled(tag, fg(theme.dim)));
+        }
+        if let Some(count) = self.instance_groups.get(&i.id()) {
+          spans.push(Span::styled(format!(" ({count})"), fg(theme.dim)));
+        }
+        ListItem::new(Line::from(spans))
       })
       .collect();
 
@@ -716,199 +3079,755 @@ impl Component for Home {
         Block::default()
           .borders(Borders::ALL)
           .border_style(if self.mode == Mode::ServiceList {
-            Style::default().fg(Color::LightGreen)
+            Style::default().fg(theme.border_focused)
           } else {
             Style::default()
           })
-          .title(" Services "),
+          .title(self.services_title()),
       )
-      .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+      .highlight_style(Style::default().bg(theme.highlight).add_modifier(Modifier::BOLD));
 
-    let chunks =
-      Layout::new(Direction::Horizontal, [Constraint::Min(30), Constraint::Percentage(100)]).split(main_panel);
-    let right_panel = chunks[1];
-
-    f.render_stateful_widget(items, chunks[0], &mut self.filtered_units.state);
+    f.render_stateful_widget(items, list_panel, &mut visible_state);
+    self.services_panel = list_panel;
 
     let selected_item = self.filtered_units.selected();
 
-    let right_panel =
-      Layout::new(Direction::Vertical, [Constraint::Min(7), Constraint::Percentage(100)]).split(right_panel);
+    // (label, value) rows for the details pane
+    let mut details_rows: Vec<(&str, Line)> = if let Some(i) = selected_item {
+      fn line_color_string<'a>(value: String, color: Color) -> Line<'a> {
+        Line::from(vec![Span::styled(value, Style::default().fg(color))])
+      }
+
+      // properties are loaded in the background; once they are, show anything systemd didn't give us as n/a
+      fn property_line<'a>(
+        properties: Option<&'a UnitProperties>,
+        value: impl Fn(&'a UnitProperties) -> Option<&'a str>,
+      ) -> Line<'a> {
+        match properties {
+          Some(p) => Line::from(value(p).unwrap_or("n/a")),
+          None => Line::default(),
+        }
+      }
+
+      fn commands_line<'a>(
+        properties: Option<&'a UnitProperties>,
+        commands: impl Fn(&'a UnitProperties) -> &'a [String],
+      ) -> Line<'a> {
+        match properties {
+          Some(p) if !commands(p).is_empty() => Line::from(commands(p).join(" ; ")),
+          Some(_) => Line::from("n/a"),
+          None => Line::default(),
+        }
+      }
+
+      // systemd doesn't expose whether the watchdog is armed, approximate it from the unit's state
+      fn watchdog_line<'a>(unit: &UnitWithStatus, properties: Option<&'a UnitProperties>, theme: &Theme) -> Line<'a> {
+        let Some(properties) = properties else { return Line::default() };
+        let Some(timeout) = properties.watchdog_usec.as_deref() else { return Line::from("n/a") };
+        let (status, color) = if properties.result.as_deref() == Some("watchdog") {
+          ("triggered", theme.state_failed)
+        } else if unit.is_active() {
+          ("armed", theme.state_active)
+        } else {
+          ("not running", theme.state_inactive)
+        };
+        Line::from(vec![
+          Span::raw(format!("{timeout} (")),
+          Span::styled(status, Style::default().fg(color)),
+          Span::raw(")"),
+        ])
+      }
+
+      // resource usage is only meaningful while the unit runs
+      fn usage_line<'a>(unit: &UnitWithStatus, value: Option<u64>, format: fn(u64) -> String) -> Line<'a> {
+        match value {
+          Some(v) if unit.is_active() => Line::from(format(v)),
+          _ => Line::default(),
+        }
+      }
+
+      fn uptime_line<'a>(unit: &UnitWithStatus, properties: Option<&UnitProperties>) -> Line<'a> {
+        match properties.and_then(|p| p.active_enter_timestamp) {
+          Some(t) if unit.is_active() => Line::from(format_uptime(chrono::Utc::now().timestamp() - t)),
+          _ => Line::default(),
+        }
+      }
+
+      let load_color = match i.load_state.as_str() {
+        "loaded" => theme.state_active,
+        "not-found" => theme.state_warning,
+        "error" => theme.state_failed,
+        _ => theme.text,
+      };
+
+      let active_color = active_state_color(&i.active_state, &theme).unwrap_or(theme.text);
+
+      let active_state_value = format!("{} ({})", i.active_state, i.sub_state);
+
+      let scope = match i.scope {
+        UnitScope::Global => "Global",
+        UnitScope::User => "User",
+      };
+
+      let health = i.health();
+      let indicator = self.health_style.indicator(health);
+      let health_line = Line::from(vec![
+        Span::styled(indicator.glyph.as_str(), Style::default().fg(indicator.color)),
+        Span::raw(" "),
+        Span::raw(health.as_str()),
+      ]);
+
+      let properties = i.properties.as_ref();
+
+      vec![
+        ("Description: ", colored_line(&i.description, theme.text)),
+        ("Scope: ", colored_line(scope, theme.text)),
+        ("Loaded: ", colored_line(&i.load_state, load_color)),
+        ("Active: ", line_color_string(active_state_value, active_color)),
+        ("Health: ", health_line),
+        ("Last action: ", last_action_line(self.last_actions.get(&i.id()), &theme, self.config.ascii)),
+        ("Note: ", Line::from(self.state.notes.get(&i.name).map(String::as_str).unwrap_or_default())),
+        ("Unit file: ", property_line(properties, |p| p.fragment_path.as_deref())),
+        ("Invocation: ", property_line(properties, |p| p.invocation_id.as_deref())),
+        ("Main PID: ", Line::from(properties.and_then(|p| p.main_pid).map(|pid| pid.to_string()).unwrap_or_default())),
+        ("Uptime: ", uptime_line(i, properties)),
+        ("ExecStartPre: ", commands_line(properties, |p| &p.exec_start_pre)),
+        ("ExecStart: ", commands_line(properties, |p| &p.exec_start)),
+        ("ExecStop: ", commands_line(properties, |p| &p.exec_stop)),
+        ("Watchdog: ", watchdog_line(i, properties, &theme)),
+        ("Memory: ", usage_line(i, properties.and_then(|p| p.memory_current), format_bytes)),
+        ("CPU: ", usage_line(i, properties.and_then(|p| p.cpu_usage_nsec), format_cpu_time)),
+        ("Last hour: ", activity_line(&self.logs, &theme, self.config.ascii)),
+      ]
+    } else {
+      vec![]
+    };
+
+    if !self.details_expanded {
+      // the last action stays in view while there is one, it's what you look for right after acting on a unit
+      details_rows
+        .retain(|(label, value)| COMPACT_DETAILS.contains(label) || (*label == "Last action: " && value.width() > 0));
+    }
+    let details_height = details_rows.len().max(COMPACT_DETAILS.len()) as u16 + 2;
+    // owned copy of the full values for the popup, the pane below may cut them off
+    let full_details = (self.mode == Mode::DetailsFull).then(|| {
+      details_rows
+        .iter()
+        .map(|(label, value)| {
+          let mut spans = vec![Span::styled(label.to_string(), Style::default().add_modifier(Modifier::BOLD))];
+          spans.extend(value.spans.iter().map(|s| Span::styled(s.content.to_string(), s.style)));
+          Line::from(spans)
+        })
+        .collect_vec()
+    });
+    let (props_lines, details_text): (Vec<Line>, Vec<Line>) =
+      details_rows.into_iter().map(|(label, value)| (Line::from(label), value)).unzip();
+
+    let right_panel = Layout::new(Direction::Vertical, [Constraint::Min(details_height), Constraint::Percentage(100)])
+      .split(right_panel);
     let details_panel = right_panel[0];
     let logs_panel = right_panel[1];
+    self.logs_viewport_height = logs_panel.height.saturating_sub(2);
+    self.logs_viewport_width = logs_panel.width.saturating_sub(2);
+    self.logs_panel = logs_panel;
 
-    let details_block = Block::default().title(" Details ").borders(Borders::ALL);
-    let details_panel_panes = Layout::new(Direction::Horizontal, [Constraint::Min(14), Constraint::Percentage(100)])
-      .split(details_block.inner(details_panel));
+    let details_block = Block::default().borders(Borders::ALL);
+    let details_panel_panes = match self.config.details_value_max_width {
+      Some(max_width) => Layout::new(Direction::Horizontal, [Constraint::Length(14), Constraint::Max(max_width)]),
+      None => Layout::new(Direction::Horizontal, [Constraint::Min(14), Constraint::Percentage(100)]),
+    }
+    .split(details_block.inner(details_panel));
     let props_pane = details_panel_panes[0];
     let values_pane = details_panel_panes[1];
 
-    let props_lines = vec![
-      Line::from("Description: "),
-      Line::from("Scope: "),
-      Line::from("Loaded: "),
-      Line::from("Active: "),
-      Line::from("Unit file: "),
-    ];
+    let value_width = values_pane.width as usize;
+    let any_cut_off = details_text.iter().any(|l| l.width() > value_width);
+    let details_text = details_text
+      .into_iter()
+      .map(|l| if l.width() > value_width { truncate_line(l, value_width) } else { l })
+      .collect_vec();
 
-    let details_text = if let Some(i) = selected_item {
-      fn line_color_string<'a>(value: String, color: Color) -> Line<'a> {
-        Line::from(vec![Span::styled(value, Style::default().fg(color))])
+    let details_hint = match (self.details_expanded, any_cut_off) {
+      (true, false) => "d to collapse",
+      (false, false) => "d to expand",
+      (true, true) => "d to collapse, V to view full",
+      (false, true) => "d to expand, V to view full",
+    };
+    let details_title = match selected_item {
+      Some(unit) => format!(" Details of {} ({details_hint}) ", self.list_name(unit)),
+      None => format!(" Details ({details_hint}) "),
+    };
+    let details_block = details_block.title(details_title);
+
+    let paragraph = Paragraph::new(details_text).style(Style::default());
+
+    let props_widget = Paragraph::new(props_lines).alignment(ratatui::layout::Alignment::Right);
+    f.render_widget(props_widget, props_pane);
+
+    f.render_widget(paragraph, values_pane);
+    f.render_widget(details_block, details_panel);
+
+    fn log_line<'a>(l: &'a str, theme: &Theme) -> Line<'a> {
+      if l.starts_with(JOURNALCTL_ERROR_PREFIX) {
+        return Line::from(Span::styled(l, Style::default().fg(theme.state_failed)));
       }
+      if let Some((date, rest)) = l.splitn(2, ' ').collect_tuple() {
+        if date.len() != 24 {
+          return Line::from(l);
+        }
+        Line::from(vec![Span::styled(date, Style::default().fg(theme.log_timestamp)), Span::raw(" "), Span::raw(rest)])
+      } else {
+        Line::from(l)
+      }
+    }
 
-      let load_color = match i.load_state.as_str() {
-        "loaded" => Color::Green,
-        "not-found" => Color::Yellow,
-        "error" => Color::Red,
-        _ => Color::White,
+    let bookmarks = selected_item.and_then(|u| self.log_bookmarks.get(&u.id()));
+    let log_search = self.log_search.value();
+    let log_lines = self
+      .logs
+      .iter()
+      .enumerate()
+      .rev()
+      .map(|(index, l)| {
+        let mut line = highlight_matches(log_line(l, &theme), log_search, &theme);
+        // numbered marker in the gutter for bookmarked lines
+        if let Some(n) = bookmarks.and_then(|b| b.iter().position(|&m| m == index)) {
+          let arrow = if self.config.ascii { '>' } else { '▶' };
+          let marker =
+            Span::styled(format!("{arrow}{} ", n + 1), Style::default().fg(theme.marker_text).bg(theme.marker));
+          line.spans.insert(0, marker);
+        }
+        line
+      })
+      .collect_vec();
+
+    let mut logs_block = Block::default().title(self.logs_title()).borders(Borders::ALL);
+    if self.logs_focused {
+      logs_block = logs_block.border_style(Style::default().fg(theme.border_focused));
+    }
+    if self.mode == Mode::LogSearch || !log_search.is_empty() {
+      let query = log_search.to_ascii_lowercase();
+      let matches = self.logs.iter().filter(|l| l.to_ascii_lowercase().contains(&query)).count();
+      logs_block = logs_block.title(
+        Title::from(Line::from(vec![
+          Span::raw(format!(" /{log_search} ")),
+          Span::styled(format!("({matches} matches, n/N: next/previous) "), Style::default().fg(theme.dim)),
+        ]))
+        .position(Position::Bottom),
+      );
+    }
+    let mut paragraph = Paragraph::new(log_lines).block(logs_block).style(Style::default());
+    paragraph = match self.log_wrap {
+      true => paragraph.wrap(Wrap { trim: true }).scroll((self.logs_scroll_offset, 0)),
+      false => paragraph.scroll((self.logs_scroll_offset, self.logs_horizontal_offset)),
+    };
+    f.render_widget(paragraph, logs_panel);
+
+    let mut search_title = vec![
+      Span::raw(" Search "),
+      Span::styled("(", Style::default().fg(theme.dim)),
+      Span::styled("ctrl+f", Style::default().add_modifier(Modifier::BOLD).fg(theme.key_hint)),
+      Span::styled(" or ", Style::default().fg(theme.dim)),
+      Span::styled("/", Style::default().add_modifier(Modifier::BOLD).fg(theme.key_hint)),
+      Span::styled(" to focus", Style::default().fg(theme.dim)),
+      Span::styled(") ", Style::default().fg(theme.dim)),
+    ];
+    // with nothing typed yet, give an overview of the system instead
+    if self.input.value().is_empty() {
+      let active = self.all_units.values().filter(|u| u.is_active()).count();
+      let failed = self.all_units.values().filter(|u| u.is_failed()).count();
+      search_title.push(Span::styled(format!("{} units · ", self.all_units.len()), Style::default().fg(theme.muted)));
+      search_title.push(Span::styled(format!("{active} active"), Style::default().fg(theme.state_active)));
+      search_title.push(Span::styled(" · ", Style::default().fg(theme.muted)));
+      let failed_color = if failed > 0 { theme.state_failed } else { theme.muted };
+      search_title.push(Span::styled(format!("{failed} failed "), Style::default().fg(failed_color)));
+    }
+
+    let width = search_panel.width.max(3) - 3; // keep 2 for borders and 1 for cursor
+    let scroll = self.input.visual_scroll(width as usize);
+    let input = Paragraph::new(self.input.value())
+      .style(match self.mode {
+        Mode::Search => Style::default().fg(theme.border_focused),
+        _ => Style::default(),
+      })
+      .scroll((0, scroll as u16))
+      .block(Block::default().borders(Borders::ALL).title(Line::from(search_title)));
+    f.render_widget(input, search_panel);
+    // clear top right of search panel so we can put help instructions there
+    let help_width = 24;
+    let help_area = Rect::new(search_panel.x + search_panel.width - help_width - 2, search_panel.y, help_width, 1);
+    f.render_widget(Clear, help_area);
+    let help_text = Paragraph::new(Line::from(vec![
+      Span::raw(" Press "),
+      Span::styled("?", Style::default().add_modifier(Modifier::BOLD).fg(theme.key_hint)),
+      Span::raw(" or "),
+      Span::styled("F1", Style::default().add_modifier(Modifier::BOLD).fg(theme.key_hint)),
+      Span::raw(" for help "),
+    ]))
+    .style(Style::default().fg(theme.dim));
+    f.render_widget(help_text, help_area);
+
+    if self.mode == Mode::LogSearch {
+      // in the bottom title, after the leading " /"
+      f.set_cursor(
+        (logs_panel.x + 3 + self.log_search.cursor() as u16).min(logs_panel.x + logs_panel.width.saturating_sub(2)),
+        logs_panel.y + logs_panel.height.saturating_sub(1),
+      )
+    }
+
+    if self.mode == Mode::Search {
+      f.set_cursor(
+        (search_panel.x + 1 + self.input.cursor() as u16).min(search_panel.x + search_panel.width - 2),
+        search_panel.y + 1,
+      )
+    }
+
+    if self.mode == Mode::Help {
+      let primary = |s: &'static str| Span::styled(s, Style::default().fg(theme.heading));
+
+      let help_lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("Shortcuts", Style::default().add_modifier(Modifier::UNDERLINED))),
+        Line::from(""),
+        Line::from(vec![primary("ctrl+C"), Span::raw(" or "), primary("ctrl+Q"), Span::raw(" to quit")]),
+        Line::from(vec![primary("ctrl+L"), Span::raw(" toggles the logger pane")]),
+        Line::from(vec![primary("ctrl+R"), Span::raw(" reload services and restart log following")]),
+        Line::from(vec![primary("PageUp"), Span::raw(" / "), primary("PageDown"), Span::raw(" scroll the logs")]),
+        Line::from(vec![primary("Home"), Span::raw(" / "), primary("End"), Span::raw(" scroll to top/bottom")]),
+        Line::from(vec![primary("r"), Span::raw(" reload the logs from scratch")]),
+        Line::from(vec![primary("R"), Span::raw(" reload systemd's configuration (daemon-reload)")]),
+        Line::from(vec![primary("L"), Span::raw(" load twice as many log lines")]),
+        Line::from(vec![primary("Enter"), Span::raw(" open the action menu")]),
+        Line::from(vec![
+          primary(":"),
+          Span::raw(" or "),
+          primary("Ctrl+P"),
+          Span::raw(" search everything there is to do"),
+        ]),
+        Line::from(vec![
+          primary("Space"),
+          Span::raw(" mark units to start/stop/restart together, "),
+          primary("Esc"),
+          Span::raw(" clears the marks"),
+        ]),
+        Line::from(vec![
+          primary("Mouse"),
+          Span::raw(" click a unit or action to pick it, scroll the logs with the wheel"),
+        ]),
+        Line::from(vec![primary("f"), Span::raw(" jump to the next failed unit")]),
+        Line::from(vec![primary("X"), Span::raw(" restart all failed units, one after another (Esc cancels)")]),
+        Line::from(vec![primary("x"), Span::raw(" only show failed units")]),
+        Line::from(vec![primary("T"), Span::raw(" show state changes seen this session")]),
+        Line::from(vec![primary("b"), Span::raw(" switch logs between the current boot and all boots")]),
+        Line::from(vec![primary("B"), Span::raw(" list boots and pick one to show logs from")]),
+        Line::from(vec![primary("p"), Span::raw(" cycle the minimum log priority (emerg..debug, all)")]),
+        Line::from(vec![primary("U"), Span::raw(" list unit files to enable/disable several at once")]),
+        Line::from(vec![primary("C"), Span::raw(" toggle including logs from child processes")]),
+        Line::from(vec![primary("d"), Span::raw(" expand/collapse the details pane")]),
+        Line::from(vec![primary("E"), Span::raw(" only show units that logged errors in the last hour")]),
+        Line::from(vec![primary("Tab"), Span::raw(" focus the logs pane (only it is shown on narrow terminals)")]),
+        Line::from(vec![primary("/"), Span::raw(" search the logs when they're focused, n/N: next/previous match")]),
+        Line::from(vec![primary("Y"), Span::raw(" copy a systemctl command that lists the units shown")]),
+        Line::from(vec![primary("A"), Span::raw(" toggle showing how long units have been in their state")]),
+        Line::from(vec![primary("u"), Span::raw(" toggle showing full unit names, with the .service suffix")]),
+        Line::from(vec![
+          primary("i"),
+          Span::raw(" toggle grouping template instances (Enter/→ expands a group, ← collapses)"),
+        ]),
+        Line::from(vec![primary("F"), Span::raw(" toggle following new logs vs a one-off snapshot")]),
+        Line::from(vec![primary("P"), Span::raw(" pause/resume new log lines for the selected unit")]),
+        Line::from(vec![primary("|"), Span::raw(" switch between the list beside or above the details and logs")]),
+        Line::from(vec![primary("w"), Span::raw(" toggle wrapping long log lines, ←/→ scroll them when focused")]),
+        Line::from(vec![primary("y"), Span::raw(" copy the logs to the clipboard")]),
+        Line::from(vec![primary("W"), Span::raw(" write the logs to <unit>-<time>.log in the current directory")]),
+        Line::from(vec![primary("c"), Span::raw(" view the unit file and drop-ins (systemctl cat)")]),
+        Line::from(vec![primary("e"), Span::raw(" edit the unit file (systemctl edit --full), then daemon-reload")]),
+        Line::from(vec![primary("s"), Span::raw(" view the full systemctl status, with the cgroup and recent logs")]),
+        Line::from(vec![primary("D"), Span::raw(" view the unit's dependencies and their states (Enter: go to one)")]),
+        Line::from(vec![primary("V"), Span::raw(" view details that were cut off in full")]),
+        Line::from(vec![primary("S"), Span::raw(" switch between system+user, system and user units")]),
+        Line::from(vec![primary("t"), Span::raw(" cycle the unit type: service, timer, socket, target, mount, all")]),
+        Line::from(vec![primary("!"), Span::raw(" invert the filter, showing units that don't match")]),
+        Line::from(vec![primary("m"), Span::raw(" bookmark the log line at the top of the logs pane")]),
+        Line::from(vec![primary("'"), Span::raw(" jump to the next bookmarked log line")]),
+        Line::from(vec![primary("?"), Span::raw(" / "), primary("F1"), Span::raw(" open this help pane")]),
+        Line::from(""),
+        Line::from(Span::styled("Vim Style Shortcuts", Style::default().add_modifier(Modifier::UNDERLINED))),
+        Line::from(""),
+        Line::from(vec![primary("j"), Span::raw(" navigate down")]),
+        Line::from(vec![primary("k"), Span::raw(" navigate up")]),
+        Line::from(vec![primary("g"), Span::raw(" / "), primary("G"), Span::raw(" jump to the first/last unit")]),
+        Line::from(vec![
+          primary("ctrl+U"),
+          Span::raw(" / "),
+          primary("ctrl+D"),
+          Span::raw(" scroll the logs half a page"),
+        ]),
+      ];
+
+      // count the rows the help takes once wrapped, so it can be scrolled when it doesn't fit on the screen
+      let width = 50u16.min(f.size().width);
+      let inner_width = width.saturating_sub(2).max(1) as usize;
+      let help_rows: usize = help_lines.iter().map(|l| l.width().max(1).div_ceil(inner_width)).sum();
+      let popup = centered_rect_abs(width, help_rows as u16 + 2, f.size());
+      self.help_scroll = self.help_scroll.min((help_rows as u16).saturating_sub(popup.height.saturating_sub(2)));
+
+      let name = env!("CARGO_PKG_NAME");
+      let version = env!("CARGO_PKG_VERSION");
+      let title = match self.config.ascii {
+        true => format!(" Help for {} v{} ", name, version),
+        false => format!(" ✨️ Help for {} v{} ✨️ ", name, version),
       };
+      let mut block = Block::default().title(title).borders(Borders::ALL);
+      if help_rows > popup.height.saturating_sub(2) as usize {
+        block = block.title(Title::from(" j/k to scroll ").position(Position::Bottom).alignment(Alignment::Right));
+      }
+
+      let paragraph = Paragraph::new(help_lines)
+        .block(block)
+        .style(Style::default())
+        .wrap(Wrap { trim: true })
+        .scroll((self.help_scroll, 0));
+
+      f.render_widget(Clear, popup);
+      f.render_widget(paragraph, popup);
+    }
 
-      let active_color = match i.active_state.as_str() {
-        "active" => Color::Green,
-        "inactive" => Color::Gray,
-        "failed" => Color::Red,
-        _ => Color::White,
+    if self.mode == Mode::Error {
+      let popup = centered_rect_abs(50, 12, f.size());
+      let error_lines = self.error_message.split('\n').map(Line::from).collect_vec();
+      let paragraph = Paragraph::new(error_lines)
+        .block(
+          Block::default()
+            .title(if self.config.ascii { " Error " } else { " ⚠️ Error ⚠️ " })
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.state_failed)),
+        )
+        .wrap(Wrap { trim: true });
+
+      f.render_widget(Clear, popup);
+      f.render_widget(paragraph, popup);
+    }
+
+    if self.mode == Mode::EditNote {
+      if let Some(selected) = self.filtered_units.selected() {
+        let popup = centered_rect_abs(60, 3, f.size());
+        let width = popup.width.max(3) - 3; // keep 2 for borders and 1 for cursor
+        let scroll = self.note_input.visual_scroll(width as usize);
+        let input = Paragraph::new(self.note_input.value()).scroll((0, scroll as u16)).block(
+          Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_focused))
+            .title(format!(" Note for {} (Enter to save, Esc to cancel) ", selected.name)),
+        );
+
+        f.render_widget(Clear, popup);
+        f.render_widget(input, popup);
+        let cursor_x = popup.x + 1 + self.note_input.cursor().saturating_sub(scroll) as u16;
+        f.set_cursor(cursor_x.min(popup.x + popup.width - 2), popup.y + 1);
+      }
+    }
+
+    if self.mode == Mode::CommandPalette {
+      let height = (self.palette_matches.items.len() as u16 + 3).clamp(5, f.size().height.saturating_sub(4).max(5));
+      let popup = centered_rect_abs(70, height, f.size());
+      let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .title(" Command palette (type to filter, Enter: run, Esc: close) ");
+      let chunks =
+        Layout::new(Direction::Vertical, [Constraint::Length(1), Constraint::Min(0)]).split(block.inner(popup));
+
+      let input = Paragraph::new(Line::from(vec![
+        Span::styled("> ", Style::default().fg(theme.dim)),
+        Span::raw(self.palette_input.value()),
+      ]));
+      let items =
+        self.palette_matches.items.iter().map(|&i| ListItem::new(self.palette_items[i].name.as_str())).collect_vec();
+      let list = List::new(items).highlight_style(Style::default().bg(theme.highlight).add_modifier(Modifier::BOLD));
+
+      f.render_widget(Clear, popup);
+      f.render_widget(block, popup);
+      f.render_widget(input, chunks[0]);
+      f.render_stateful_widget(list, chunks[1], &mut self.palette_matches.state);
+      let cursor_x = chunks[0].x + 2 + self.palette_input.cursor() as u16;
+      f.set_cursor(cursor_x.min(popup.x + popup.width - 2), chunks[0].y);
+    }
+
+    if self.mode == Mode::DropIns {
+      let width = self.drop_ins.items.iter().map(|p| p.len() as u16).max().unwrap_or(0) + 4;
+      let popup = centered_rect_abs(width.max(40), self.drop_ins.items.len() as u16 + 2, f.size());
+
+      let items: Vec<ListItem> = self.drop_ins.items.iter().map(|p| ListItem::new(p.as_str())).collect();
+      let items = List::new(items)
+        .block(
+          Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_focused))
+            .title(" Drop-ins (Enter: view, e: edit) "),
+        )
+        .highlight_style(Style::default().bg(theme.highlight).add_modifier(Modifier::BOLD));
+
+      f.render_widget(Clear, popup);
+      f.render_stateful_widget(items, popup, &mut self.drop_ins.state);
+    }
+
+    if self.mode == Mode::UnitFiles {
+      let items = self
+        .unit_files
+        .items
+        .iter()
+        .map(|file| {
+          let marker = if self.marked_unit_files.contains(&file.id()) { "[x] " } else { "[ ] " };
+          let state_color = match file.state.as_str() {
+            "enabled" | "enabled-runtime" => theme.state_active,
+            "masked" | "masked-runtime" | "bad" => theme.state_failed,
+            _ => theme.state_inactive,
+          };
+          let mut spans = vec![
+            Span::raw(marker),
+            Span::raw(file.name.as_str()),
+            Span::raw(" "),
+            Span::styled(file.state.as_str(), Style::default().fg(state_color)),
+          ];
+          if matches!(self.scope, Scope::All) && file.scope == UnitScope::User {
+            spans.push(Span::styled(" (user)", Style::default().fg(theme.dim)));
+          }
+          ListItem::new(Line::from(spans))
+        })
+        .collect_vec();
+
+      let popup = centered_rect_abs(80, f.size().height.saturating_sub(4), f.size());
+      let list = List::new(items)
+        .block(
+          Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_focused))
+            .title(" Unit files (Space: mark, e: enable, d: disable) "),
+        )
+        .highlight_style(Style::default().bg(theme.highlight).add_modifier(Modifier::BOLD));
+
+      f.render_widget(Clear, popup);
+      f.render_stateful_widget(list, popup, &mut self.unit_files.state);
+    }
+
+    if self.mode == Mode::Boots {
+      let items = self
+        .boots
+        .items
+        .iter()
+        .map(|boot| match boot {
+          None => ListItem::new("All boots"),
+          Some(b) => {
+            let duration = b.duration.map(|d| format!(" ({})", format_age(d.num_seconds()))).unwrap_or_default();
+            ListItem::new(Line::from(vec![
+              Span::styled(format!("{:>4} ", b.offset), Style::default().fg(theme.muted)),
+              Span::raw(format!("{} → {}", b.first_entry, b.last_entry)),
+              Span::styled(duration, Style::default().fg(theme.dim)),
+            ]))
+          },
+        })
+        .collect_vec();
+
+      // without a persistent journal only the current boot is known
+      let title = if self.boots.items.len() <= 2 {
+        " Boots (only the current boot is in the journal, it may not be persistent) "
+      } else {
+        " Boots (Enter: show logs from boot) "
       };
+      let width = items.iter().map(|i| i.width() as u16).max().unwrap_or(0).max(title.len() as u16) + 4;
+      let height = (items.len() as u16 + 2).min(f.size().height.saturating_sub(2));
+      let popup = centered_rect_abs(width, height, f.size());
+
+      let list = List::new(items)
+        .block(
+          Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border_focused)).title(title),
+        )
+        .highlight_style(Style::default().bg(theme.highlight).add_modifier(Modifier::BOLD));
+
+      f.render_widget(Clear, popup);
+      f.render_stateful_widget(list, popup, &mut self.boots.state);
+    }
+
+    if let Some(lines) = full_details {
+      let inner_width = f.size().width.min(100).saturating_sub(2).max(1) as usize;
+      let height = lines.iter().map(|l| l.width().max(1).div_ceil(inner_width)).sum::<usize>() as u16 + 2;
+      let popup = centered_rect_abs(100, height, f.size());
+      let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+          .title(" Details ")
+          .borders(Borders::ALL)
+          .border_style(Style::default().fg(theme.border_focused)),
+      );
+
+      f.render_widget(Clear, popup);
+      f.render_widget(paragraph, popup);
+    }
 
-      let active_state_value = format!("{} ({})", i.active_state, i.sub_state);
+    if self.mode == Mode::UnitDiff {
+      let popup = centered_rect_abs(100, f.size().height.saturating_sub(4), f.size());
+      let lines = self
+        .unit_diff
+        .iter()
+        .map(|l| match l.chars().next() {
+          Some('+') => Line::from(Span::styled(l.as_str(), Style::default().fg(theme.state_active))),
+          Some('-') => Line::from(Span::styled(l.as_str(), Style::default().fg(theme.state_failed))),
+          _ => Line::from(Span::styled(l.as_str(), Style::default().fg(theme.dim))),
+        })
+        .collect_vec();
 
-      let scope = match i.scope {
-        UnitScope::Global => "Global",
-        UnitScope::User => "User",
-      };
+      let paragraph = Paragraph::new(lines).scroll((self.unit_diff_scroll, 0)).block(
+        Block::default()
+          .title(" Vendor unit file → effective config (j/k: scroll, Esc: close) ")
+          .borders(Borders::ALL)
+          .border_style(Style::default().fg(theme.border_focused)),
+      );
 
-      let mut lines = vec![
-        colored_line(&i.description, Color::White),
-        colored_line(scope, Color::White),
-        colored_line(&i.load_state, load_color),
-        line_color_string(active_state_value, active_color),
-      ];
+      f.render_widget(Clear, popup);
+      f.render_widget(paragraph, popup);
+    }
 
-      if let Some(file_path) = &i.file_path {
-        lines.push(Line::from(file_path.as_str()));
-      }
+    if self.mode == Mode::UnitCat {
+      let popup = centered_rect_abs(100, f.size().height.saturating_sub(4), f.size());
+      // systemctl puts a `# /path/to/file` header before the unit file and each drop-in, make those stand out
+      let lines = self
+        .unit_cat
+        .iter()
+        .map(|l| {
+          if l.starts_with("# /") {
+            Line::from(Span::styled(l.as_str(), Style::default().fg(theme.heading).add_modifier(Modifier::BOLD)))
+          } else {
+            Line::from(l.as_str())
+          }
+        })
+        .collect_vec();
 
-      lines
-    } else {
-      vec![]
-    };
+      let paragraph = Paragraph::new(lines).scroll((self.unit_cat_scroll, 0)).block(
+        Block::default()
+          .title(" systemctl cat (j/k: scroll, Esc: close) ")
+          .borders(Borders::ALL)
+          .border_style(Style::default().fg(theme.border_focused)),
+      );
 
-    let paragraph = Paragraph::new(details_text).style(Style::default());
+      f.render_widget(Clear, popup);
+      f.render_widget(paragraph, popup);
+    }
 
-    let props_widget = Paragraph::new(props_lines).alignment(ratatui::layout::Alignment::Right);
-    f.render_widget(props_widget, props_pane);
+    if self.mode == Mode::Status {
+      let popup = centered_rect_abs(120, f.size().height.saturating_sub(4), f.size());
+      let paragraph = Paragraph::new(self.status_lines.clone()).scroll((self.status_scroll, 0)).block(
+        Block::default()
+          .title(" systemctl status (j/k: scroll, Esc: close) ")
+          .borders(Borders::ALL)
+          .border_style(Style::default().fg(theme.border_focused)),
+      );
 
-    f.render_widget(paragraph, values_pane);
-    f.render_widget(details_block, details_panel);
+      f.render_widget(Clear, popup);
+      f.render_widget(paragraph, popup);
+    }
 
-    let log_lines = self
-      .logs
-      .iter()
-      .rev()
-      .map(|l| {
-        if let Some((date, rest)) = l.splitn(2, ' ').collect_tuple() {
-          if date.len() != 24 {
-            return Line::from(l.as_str());
-          }
-          Line::from(vec![Span::styled(date, Style::default().fg(Color::DarkGray)), Span::raw(" "), Span::raw(rest)])
-        } else {
-          Line::from(l.as_str())
-        }
-      })
-      .collect_vec();
+    if let (Mode::Dependencies, Some(unit)) = (self.mode, &self.dependencies_of) {
+      let mut previous_kind = "";
+      let items = self
+        .dependencies
+        .items
+        .iter()
+        .map(|(kind, name)| {
+          // only label the first unit of each kind
+          let label = if *kind == previous_kind { "" } else { *kind };
+          previous_kind = *kind;
+          let (state, color) = match self.dependency_states.get(name).map(String::as_str) {
+            Some(state @ "active") => (state, theme.state_active),
+            Some(state @ "failed") => (state, theme.state_failed),
+            Some(state @ "inactive") => (state, theme.state_inactive),
+            Some(state) => (state, theme.state_warning),
+            None => ("not loaded", theme.dim),
+          };
+          ListItem::new(Line::from(vec![
+            Span::styled(format!("{label:<9} "), Style::default().fg(theme.heading).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{name} ")),
+            Span::styled(format!("({state})"), Style::default().fg(color)),
+          ]))
+        })
+        .collect_vec();
 
-    let paragraph = Paragraph::new(log_lines)
-      .block(Block::default().title(" Service Logs ").borders(Borders::ALL))
-      .style(Style::default())
-      .wrap(Wrap { trim: true })
-      .scroll((self.logs_scroll_offset, 0));
-    f.render_widget(paragraph, logs_panel);
+      let title = if items.is_empty() {
+        format!(" {} has no dependencies ", unit.name)
+      } else {
+        format!(" Dependencies of {} (Enter: go to unit, Esc: close) ", unit.name)
+      };
+      let width = items.iter().map(|i| i.width() as u16).max().unwrap_or(0).max(title.len() as u16) + 4;
+      let height = (items.len() as u16 + 2).min(f.size().height.saturating_sub(2));
+      let popup = centered_rect_abs(width, height, f.size());
 
-    let width = search_panel.width.max(3) - 3; // keep 2 for borders and 1 for cursor
-    let scroll = self.input.visual_scroll(width as usize);
-    let input = Paragraph::new(self.input.value())
-      .style(match self.mode {
-        Mode::Search => Style::default().fg(Color::LightGreen),
-        _ => Style::default(),
-      })
-      .scroll((0, scroll as u16))
-      .block(Block::default().borders(Borders::ALL).title(Line::from(vec![
-        Span::raw(" Search "),
-        Span::styled("(", Style::default().fg(Color::DarkGray)),
-        Span::styled("ctrl+f", Style::default().add_modifier(Modifier::BOLD).fg(Color::Gray)),
-        Span::styled(" or ", Style::default().fg(Color::DarkGray)),
-        Span::styled("/", Style::default().add_modifier(Modifier::BOLD).fg(Color::Gray)),
-        Span::styled(" to focus", Style::default().fg(Color::DarkGray)),
-        Span::styled(") ", Style::default().fg(Color::DarkGray)),
-      ])));
-    f.render_widget(input, search_panel);
-    // clear top right of search panel so we can put help instructions there
-    let help_width = 24;
-    let help_area = Rect::new(search_panel.x + search_panel.width - help_width - 2, search_panel.y, help_width, 1);
-    f.render_widget(Clear, help_area);
-    let help_text = Paragraph::new(Line::from(vec![
-      Span::raw(" Press "),
-      Span::styled("?", Style::default().add_modifier(Modifier::BOLD).fg(Color::Gray)),
-      Span::raw(" or "),
-      Span::styled("F1", Style::default().add_modifier(Modifier::BOLD).fg(Color::Gray)),
-      Span::raw(" for help "),
-    ]))
-    .style(Style::default().fg(Color::DarkGray));
-    f.render_widget(help_text, help_area);
+      let list = List::new(items)
+        .block(
+          Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border_focused)).title(title),
+        )
+        .highlight_style(Style::default().bg(theme.highlight).add_modifier(Modifier::BOLD));
 
-    if self.mode == Mode::Search {
-      f.set_cursor(
-        (search_panel.x + 1 + self.input.cursor() as u16).min(search_panel.x + search_panel.width - 2),
-        search_panel.y + 1,
-      )
+      f.render_widget(Clear, popup);
+      f.render_stateful_widget(list, popup, &mut self.dependencies.state);
     }
 
-    if self.mode == Mode::Help {
-      let popup = centered_rect_abs(50, 18, f.size());
-
-      fn primary(s: &str) -> Span {
-        Span::styled(s, Style::default().fg(Color::Cyan))
+    if let (Mode::Confirm, Some(confirmation)) = (self.mode, &self.confirmation) {
+      let mut lines = confirmation.prompt.lines().map(Line::from).collect_vec();
+      if !confirmation.items.is_empty() {
+        lines.push(Line::from(""));
+        lines.extend(confirmation.items.iter().map(|item| Line::from(format!("  {item}"))));
       }
+      lines.push(Line::from(""));
+      lines.push(Line::from(vec![
+        Span::styled("y", Style::default().add_modifier(Modifier::BOLD).fg(theme.key_hint)),
+        Span::raw(" to confirm, "),
+        Span::styled("n", Style::default().add_modifier(Modifier::BOLD).fg(theme.key_hint)),
+        Span::raw(" to cancel"),
+      ]));
 
-      let help_lines = vec![
-        Line::from(""),
-        Line::from(Span::styled("Shortcuts", Style::default().add_modifier(Modifier::UNDERLINED))),
-        Line::from(""),
-        Line::from(vec![primary("ctrl+C"), Span::raw(" or "), primary("ctrl+Q"), Span::raw(" to quit")]),
-        Line::from(vec![primary("ctrl+L"), Span::raw(" toggles the logger pane")]),
-        Line::from(vec![primary("PageUp"), Span::raw(" / "), primary("PageDown"), Span::raw(" scroll the logs")]),
-        Line::from(vec![primary("Home"), Span::raw(" / "), primary("End"), Span::raw(" scroll to top/bottom")]),
-        Line::from(vec![primary("Enter"), Span::raw(" or "), primary("Space"), Span::raw(" open the action menu")]),
-        Line::from(vec![primary("?"), Span::raw(" / "), primary("F1"), Span::raw(" open this help pane")]),
-        Line::from(""),
-        Line::from(Span::styled("Vim Style Shortcuts", Style::default().add_modifier(Modifier::UNDERLINED))),
-        Line::from(""),
-        Line::from(vec![primary("j"), Span::raw(" navigate down")]),
-        Line::from(vec![primary("k"), Span::raw(" navigate up")]),
-        Line::from(vec![primary("ctrl+U"), Span::raw(" / "), primary("ctrl+D"), Span::raw(" scroll the logs")]),
-      ];
-
-      let name = env!("CARGO_PKG_NAME");
-      let version = env!("CARGO_PKG_VERSION");
-      let title = format!(" ✨️ Help for {} v{} ✨️ ", name, version);
+      let width = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0) + 4;
+      let height = (lines.len() as u16 + 2).min(f.size().height.saturating_sub(2));
+      let popup = centered_rect_abs(width.max(40), height, f.size());
 
-      let paragraph = Paragraph::new(help_lines)
-        .block(Block::default().title(title).borders(Borders::ALL))
-        .style(Style::default())
-        .wrap(Wrap { trim: true });
+      let paragraph = Paragraph::new(lines).block(
+        Block::default()
+          .title(" Confirm ")
+          .borders(Borders::ALL)
+          .border_style(Style::default().fg(theme.border_focused)),
+      );
 
       f.render_widget(Clear, popup);
       f.render_widget(paragraph, popup);
     }
 
-    if self.mode == Mode::Error {
-      let popup = centered_rect_abs(50, 12, f.size());
-      let error_lines = self.error_message.split('\n').map(Line::from).collect_vec();
-      let paragraph = Paragraph::new(error_lines)
-        .block(
-          Block::default().title(" ⚠️ Error ⚠️ ").borders(Borders::ALL).border_style(Style::default().fg(Color::Red)),
-        )
-        .wrap(Wrap { trim: true });
+    if self.mode == Mode::StateChanges {
+      let popup = centered_rect_abs(100, 22, f.size());
+
+      // newest first, so the most recent changes are always visible
+      let lines = if self.state_changes.is_empty() {
+        vec![Line::from("No state changes seen yet")]
+      } else {
+        self
+          .state_changes
+          .iter()
+          .rev()
+          .map(|c| {
+            Line::from(vec![
+              Span::styled(c.time.format("%H:%M:%S").to_string(), Style::default().fg(theme.dim)),
+              Span::raw(format!(" {}: ", c.unit.name)),
+              Span::raw(c.from.as_str()),
+              Span::styled(" → ", Style::default().fg(theme.dim)),
+              Span::raw(c.to.as_str()),
+            ])
+          })
+          .collect_vec()
+      };
+
+      let paragraph = Paragraph::new(lines).block(
+        Block::default()
+          .title(" State changes ")
+          .borders(Borders::ALL)
+          .border_style(Style::default().fg(theme.border_focused)),
+      );
 
       f.render_widget(Clear, popup);
       f.render_widget(paragraph, popup);
@@ -919,42 +3838,54 @@ impl Component for Home {
       None => return,
     };
 
-    let min_width = selected_item.name.len() as u16 + 14;
+    let longest_menu_item = self.menu_items.items.iter().map(|i| i.name.len() as u16).max().unwrap_or(0);
+    let min_width = (selected_item.name.len() as u16 + 14).max(longest_menu_item + 2);
     let desired_width = min_width + 4; // idk, looks alright
     let popup_width = desired_width.min(f.size().width);
 
     if self.mode == Mode::ActionMenu {
-      let height = self.menu_items.items.len() as u16 + 2;
+      // keep the menu within the terminal; if the actions don't all fit, the list scrolls with the selection
+      let item_count = self.menu_items.items.len();
+      let max_height = f.size().height.saturating_sub(2).max(3);
+      let height = (item_count as u16 + 2).min(max_height);
       let popup = centered_rect_abs(popup_width, height, f.size());
 
+      let mut title = format!("Actions for {}", selected_item.name);
+      if item_count as u16 + 2 > height {
+        let position = self.menu_items.state.selected().map_or(0, |i| i + 1);
+        title.push_str(&format!(" ({}/{})", position, item_count));
+      }
+
       let items: Vec<ListItem> = self.menu_items.items.iter().map(|i| ListItem::new(i.name.as_str())).collect();
       let items = List::new(items)
         .block(
-          Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::LightGreen))
-            .title(format!("Actions for {}", self.filtered_units.selected().unwrap().name)),
+          Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border_focused)).title(title),
         )
-        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+        .highlight_style(Style::default().bg(theme.highlight).add_modifier(Modifier::BOLD));
 
       f.render_widget(Clear, popup);
       f.render_stateful_widget(items, popup, &mut self.menu_items.state);
+      self.action_menu_panel = popup;
     }
 
     if self.mode == Mode::Processing {
-      let height = self.menu_items.items.len() as u16 + 2;
+      // at least one row for the spinner, e.g. when nothing was picked from the menu
+      let height = (self.menu_items.items.len() as u16).max(1) + 2;
       let popup = centered_rect_abs(popup_width, height, f.size());
 
       static SPINNER_CHARS: &[char] = &['⣷', '⣯', '⣟', '⡿', '⢿', '⣻', '⣽', '⣾'];
+      static ASCII_SPINNER_CHARS: &[char] = &['|', '/', '-', '\\'];
 
-      let spinner_char = SPINNER_CHARS[self.spinner_tick as usize % SPINNER_CHARS.len()];
+      let spinner_chars = if self.config.ascii { ASCII_SPINNER_CHARS } else { SPINNER_CHARS };
+      let spinner_char = spinner_chars[self.spinner_tick as usize % spinner_chars.len()];
       // TODO: make this a spinner
-      let paragraph = Paragraph::new(vec![Line::from(format!("{}", spinner_char))])
+      let status = self.processing_status.as_deref().unwrap_or_default();
+      let paragraph = Paragraph::new(vec![Line::from(format!("{} {}", spinner_char, status))])
         .block(
           Block::default()
             .title("Processing")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::LightGreen)),
+            .border_style(Style::default().fg(theme.border_focused)),
         )
         .style(Style::default())
         .wrap(Wrap { trim: true });
@@ -965,6 +3896,387 @@ impl Component for Home {
   }
 }
 
+// The row of a bordered list that (column, row) is on, if it's inside the list
+fn list_row_at(area: Rect, column: u16, row: u16) -> Option<usize> {
+  let inside =
+    column > area.x && column < area.right().saturating_sub(1) && row > area.y && row < area.bottom().saturating_sub(1);
+  inside.then(|| (row - area.y - 1) as usize)
+}
+
+fn spawn_spinner(tx: UnboundedSender<Action>) -> JoinHandle<()> {
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_millis(200));
+    loop {
+      interval.tick().await;
+      tx.send(Action::SpinnerTick).unwrap();
+    }
+  })
+}
+
+// Whether the unit logged anything at error priority or worse in the last hour
+async fn logged_recent_errors(unit: UnitId) -> (UnitId, bool) {
+  let mut args =
+    vec!["--quiet", "--priority=err", "--since=-1h", "--lines=1", "--output=cat", "-u", unit.name.as_str()];
+  if unit.scope == UnitScope::User {
+    args.push("--user");
+  }
+  let mut command = systemd::tokio_cmd("journalctl", args);
+  command.stderr(Stdio::null());
+
+  let has_errors = match command.output().await {
+    Ok(output) => !output.stdout.is_empty(),
+    Err(e) => {
+      warn!("Error checking {} for recent errors: {}", unit.name, e);
+      false
+    },
+  };
+  (unit, has_errors)
+}
+
+// Run a program from an env var like $EDITOR (which may include args) on a file, falling back to a default
+fn command_from_env(var: &str, default: &str, file: &str) -> Action {
+  let command = std::env::var(var).ok().filter(|c| !c.trim().is_empty()).unwrap_or_else(|| default.into());
+  let mut parts = command.split_whitespace().map(String::from);
+  let program = parts.next().unwrap_or_else(|| default.into());
+  let mut args = parts.collect_vec();
+  args.push(file.into());
+  Action::RunInTerminal { program, args, then: None }
+}
+
+// Args that select the journal entries belonging to a unit, shared by the batch and follow commands
+fn journalctl_filter_args(query: &LogQuery, properties: &UnitProperties) -> Vec<String> {
+  let unit = &query.unit;
+  let mut args = match &properties.invocation_id {
+    Some(id) if query.current_invocation_only => vec![format!("_SYSTEMD_INVOCATION_ID={id}")],
+    _ if query.include_children => {
+      // `-u` misses output that journald couldn't attribute to the unit, e.g. from short-lived child processes.
+      // Match the unit's cgroup and main PID directly too; `+` ORs the matches together
+      let (unit_field, message_field) = match unit.scope {
+        UnitScope::Global => ("_SYSTEMD_UNIT", "UNIT"),
+        UnitScope::User => ("_SYSTEMD_USER_UNIT", "USER_UNIT"),
+      };
+      let mut matches = vec![format!("{unit_field}={}", unit.name), format!("{message_field}={}", unit.name)];
+      if let Some(cgroup) = &properties.control_group {
+        matches.push(format!("_SYSTEMD_CGROUP={cgroup}"));
+      }
+      if let Some(pid) = properties.main_pid {
+        matches.push(format!("_PID={pid}"));
+      }
+      matches.into_iter().flat_map(|m| ["+".to_string(), m]).skip(1).collect()
+    },
+    _ => vec!["-u".into(), unit.name.clone()],
+  };
+
+  if let Some(boot) = query.boot {
+    args.push(format!("--boot={boot}"));
+  }
+
+  if let Some(priority) = query.priority {
+    args.push(format!("--priority={priority}"));
+  }
+
+  if unit.scope == UnitScope::User {
+    args.push("--user".into());
+  }
+
+  args
+}
+
+// The color for an ActiveState, if it has one. Units that are on their way up or down stand out like a warning
+fn active_state_color(active_state: &str, theme: &Theme) -> Option<Color> {
+  match active_state {
+    "active" => Some(theme.state_active),
+    "inactive" => Some(theme.state_inactive),
+    "failed" => Some(theme.state_failed),
+    "activating" | "deactivating" | "reloading" | "refreshing" => Some(theme.state_warning),
+    _ => None,
+  }
+}
+
+// Turn a line with ANSI colors (SGR escape sequences) into styled spans. Other escape sequences are dropped
+fn ansi_line(text: &str) -> Line<'static> {
+  let mut spans = vec![];
+  let mut style = Style::default();
+  let mut rest = text;
+  while let Some(start) = rest.find('\x1b') {
+    if start > 0 {
+      spans.push(Span::styled(rest[..start].to_string(), style));
+    }
+    rest = &rest[start + 1..];
+    // CSI sequences are `ESC [ parameters final-byte`, only the `m` ones change the style
+    let Some(sequence) = rest.strip_prefix('[') else { continue };
+    let Some(end) = sequence.find(|c: char| ('@'..='~').contains(&c)) else {
+      rest = "";
+      break;
+    };
+    if sequence[end..].starts_with('m') {
+      style = apply_sgr(style, &sequence[..end]);
+    }
+    rest = &sequence[end + 1..];
+  }
+  if !rest.is_empty() {
+    spans.push(Span::styled(rest.to_string(), style));
+  }
+  Line::from(spans)
+}
+
+// Apply SGR parameters like `1;31` to a style. The basic colors are palette indexes, so they look like they do
+// in the user's terminal
+fn apply_sgr(mut style: Style, parameters: &str) -> Style {
+  // an empty parameter means 0, e.g. `ESC [ m` is a reset
+  let mut codes = parameters.split(';').map(|c| c.parse::<u8>().unwrap_or(0));
+  while let Some(code) = codes.next() {
+    style = match code {
+      0 => Style::default(),
+      1 => style.add_modifier(Modifier::BOLD),
+      2 => style.add_modifier(Modifier::DIM),
+      3 => style.add_modifier(Modifier::ITALIC),
+      4 => style.add_modifier(Modifier::UNDERLINED),
+      22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+      23 => style.remove_modifier(Modifier::ITALIC),
+      24 => style.remove_modifier(Modifier::UNDERLINED),
+      30..=37 => style.fg(Color::Indexed(code - 30)),
+      39 => style.fg(Color::Reset),
+      40..=47 => style.bg(Color::Indexed(code - 40)),
+      49 => style.bg(Color::Reset),
+      90..=97 => style.fg(Color::Indexed(code - 90 + 8)),
+      100..=107 => style.bg(Color::Indexed(code - 100 + 8)),
+      38 | 48 => {
+        let color = match codes.next() {
+          Some(5) => codes.next().map(Color::Indexed),
+          Some(2) => match (codes.next(), codes.next(), codes.next()) {
+            (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+            _ => None,
+          },
+          _ => None,
+        };
+        match color {
+          Some(color) if code == 38 => style.fg(color),
+          Some(color) => style.bg(color),
+          None => style,
+        }
+      },
+      _ => style,
+    };
+  }
+  style
+}
+
+/// Cut a line off at `width` characters, ending it with an ellipsis and keeping the styles of the spans
+fn truncate_line(line: Line<'_>, width: usize) -> Line<'_> {
+  let mut remaining = width.saturating_sub(1);
+  let mut spans = vec![];
+  for span in line.spans {
+    if remaining == 0 {
+      break;
+    }
+    let content: String = span.content.chars().take(remaining).collect();
+    remaining -= content.chars().count();
+    spans.push(Span::styled(content, span.style));
+  }
+  spans.push(Span::raw("…"));
+  Line::from(spans)
+}
+
+/// `systemctl cat` output without the `# /path/to/file` comment before each of the given files (and the blank line
+/// separating them), so only the configuration itself gets compared
+fn strip_cat_headers(output: &str, paths: &[String]) -> String {
+  let mut lines: Vec<&str> = vec![];
+  for line in output.lines() {
+    if line.strip_prefix("# ").is_some_and(|path| paths.iter().any(|p| p == path)) {
+      if lines.last() == Some(&"") {
+        lines.pop();
+      }
+      continue;
+    }
+    lines.push(line);
+  }
+  lines.into_iter().map(|line| format!("{line}\n")).collect()
+}
+
+/// Line diff from the vendor unit file to the effective config, each line prefixed with `+`, `-` or a space
+fn unit_diff(vendor: &str, effective: &str) -> Vec<String> {
+  similar::TextDiff::from_lines(vendor, effective)
+    .iter_all_changes()
+    .map(|change| {
+      let sign = match change.tag() {
+        similar::ChangeTag::Delete => '-',
+        similar::ChangeTag::Insert => '+',
+        similar::ChangeTag::Equal => ' ',
+      };
+      format!("{}{}", sign, change.value().trim_end_matches('\n'))
+    })
+    .collect()
+}
+
+/// e.g. "Restart ✓ 2m ago", empty if nothing was done to the unit yet
+fn last_action_line<'a>(last_action: Option<&LastAction>, theme: &Theme, ascii: bool) -> Line<'a> {
+  let Some(last_action) = last_action else { return Line::default() };
+  let (mark, color) = match (last_action.succeeded, ascii) {
+    (true, false) => ("✓", theme.state_active),
+    (true, true) => ("ok", theme.state_active),
+    (false, false) => ("✗", theme.state_failed),
+    (false, true) => ("failed", theme.state_failed),
+  };
+  let age = format_age((chrono::Local::now() - last_action.time).num_seconds());
+  Line::from(vec![
+    Span::raw(format!("{} ", last_action.name)),
+    Span::styled(mark, Style::default().fg(color)),
+    Span::styled(format!(" {age} ago"), Style::default().fg(theme.dim)),
+  ])
+}
+
+/// Humanize a duration in seconds as its largest unit, e.g. "45s", "12m", "3d"
+fn format_age(seconds: i64) -> String {
+  match seconds.max(0) {
+    s if s < 60 => format!("{s}s"),
+    s if s < 60 * 60 => format!("{}m", s / 60),
+    s if s < 24 * 60 * 60 => format!("{}h", s / (60 * 60)),
+    s => format!("{}d", s / (24 * 60 * 60)),
+  }
+}
+
+/// Humanize a duration in seconds down to the minute, e.g. "3d 4h 12m", "5h 0m", "45s"
+fn format_uptime(seconds: i64) -> String {
+  let s = seconds.max(0);
+  let (days, hours, minutes) = (s / (24 * 60 * 60), s / (60 * 60) % 24, s / 60 % 60);
+  match (days, hours, minutes) {
+    (0, 0, 0) => format!("{s}s"),
+    (0, 0, m) => format!("{m}m"),
+    (0, h, m) => format!("{h}h {m}m"),
+    (d, h, m) => format!("{d}d {h}h {m}m"),
+  }
+}
+
+/// Highlight the parts of a line that contain the search query, ignoring ASCII case
+fn highlight_matches<'a>(line: Line<'a>, query: &str, theme: &Theme) -> Line<'a> {
+  if query.is_empty() {
+    return line;
+  }
+  let query = query.to_ascii_lowercase();
+  let mut spans = vec![];
+  for span in line.spans {
+    // ASCII lowercasing keeps byte offsets, so match positions carry over to the original content
+    let lowercase = span.content.to_ascii_lowercase();
+    let mut last = 0;
+    for (start, _) in lowercase.match_indices(&query) {
+      let end = start + query.len();
+      spans.push(Span::styled(span.content[last..start].to_string(), span.style));
+      spans.push(Span::styled(span.content[start..end].to_string(), span.style.fg(theme.marker_text).bg(theme.marker)));
+      last = end;
+    }
+    spans.push(Span::styled(span.content[last..].to_string(), span.style));
+  }
+  Line::from(spans)
+}
+
+/// Split text into spans with the characters at `indices` (as fuzzy matching reports them) underlined and bold
+fn highlight_chars<'a>(text: &'a str, indices: &[usize], style: Style) -> Vec<Span<'a>> {
+  let matched_style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+  let mut spans = vec![];
+  let mut start = 0;
+  let mut start_matched = false;
+  for (i, (byte, _)) in text.char_indices().enumerate() {
+    let matched = indices.contains(&i);
+    if matched != start_matched && byte > start {
+      spans.push(Span::styled(&text[start..byte], if start_matched { matched_style } else { style }));
+      start = byte;
+    }
+    start_matched = matched;
+  }
+  spans.push(Span::styled(&text[start..], if start_matched { matched_style } else { style }));
+  spans
+}
+
+/// Humanize a byte count with binary units, e.g. "512 B", "42.1 MiB"
+fn format_bytes(bytes: u64) -> String {
+  const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+  if bytes < 1024 {
+    return format!("{bytes} B");
+  }
+  let mut value = bytes as f64 / 1024.0;
+  let mut unit = 0;
+  while value >= 1024.0 && unit < UNITS.len() - 1 {
+    value /= 1024.0;
+    unit += 1;
+  }
+  format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Humanize CPU time in nanoseconds like systemctl status does, e.g. "120ms", "1.3s", "2min 5s"
+fn format_cpu_time(nsec: u64) -> String {
+  let millis = nsec / 1_000_000;
+  match millis {
+    ms if ms < 1000 => format!("{ms}ms"),
+    ms if ms < 60 * 1000 => format!("{:.1}s", ms as f64 / 1000.0),
+    ms if ms < 60 * 60 * 1000 => format!("{}min {}s", ms / 60_000, ms % 60_000 / 1000),
+    ms => format!("{}h {}min", ms / 3_600_000, ms % 3_600_000 / 60_000),
+  }
+}
+
+/// Move bookmarks (indexes into `old`) to the same lines in `new`, the reloaded logs. The reload usually ends with
+/// the same lines, plus any that came in since; bookmarks on lines that are no longer there are dropped
+fn remap_log_bookmarks(bookmarks: &mut Vec<usize>, old: &[String], new: &[String]) {
+  // where the newest of the old lines ended up
+  let Some(end) = old.last().and_then(|last| new.iter().rposition(|l| l == last)) else {
+    bookmarks.clear();
+    return;
+  };
+  let shift = end as isize - (old.len() as isize - 1);
+  bookmarks.retain_mut(|index| {
+    let Some(new_index) = index.checked_add_signed(shift) else { return false };
+    let same_line = old.get(*index).is_some_and(|line| new.get(new_index) == Some(line));
+    *index = new_index;
+    same_line
+  });
+}
+
+/// The timestamp at the start of a short-iso log line
+fn log_line_time(line: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+  let (date, _) = line.split_once(' ')?;
+  chrono::DateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S%z").ok()
+}
+
+/// Count log lines per time bucket, oldest bucket first. Lines without a short-iso timestamp are ignored
+fn log_histogram(logs: &[String], now: chrono::DateTime<chrono::Local>) -> Vec<usize> {
+  let mut buckets = vec![0; ACTIVITY_BUCKETS];
+  for line in logs {
+    let Some(time) = log_line_time(line) else { continue };
+    let age = now.signed_duration_since(time);
+    if age < chrono::Duration::zero() {
+      continue;
+    }
+    let index = (age.num_seconds() / ACTIVITY_BUCKET_SECONDS) as usize;
+    if index < ACTIVITY_BUCKETS {
+      buckets[ACTIVITY_BUCKETS - 1 - index] += 1;
+    }
+  }
+  buckets
+}
+
+/// Render the log volume of the last hour as a row of block characters, scaled to the busiest bucket
+fn activity_line(logs: &[String], theme: &Theme, ascii: bool) -> Line<'static> {
+  const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+  const ASCII_BARS: [char; 8] = ['.', ',', ':', ';', '=', '+', '*', '#'];
+  let bar_chars = if ascii { ASCII_BARS } else { BARS };
+
+  let buckets = log_histogram(logs, chrono::Local::now());
+  let total: usize = buckets.iter().sum();
+  let max = buckets.iter().copied().max().unwrap_or(0);
+  if max == 0 {
+    return Line::from(Span::styled("no logs", Style::default().fg(theme.dim)));
+  }
+
+  let bars: String = buckets
+    .iter()
+    .map(|&count| if count == 0 { ' ' } else { bar_chars[(count * (bar_chars.len() - 1)).div_ceil(max)] })
+    .collect();
+  Line::from(vec![
+    Span::styled(bars, Style::default().fg(theme.heading)),
+    Span::styled(format!(" {total} lines"), Style::default().fg(theme.dim)),
+  ])
+}
+
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
 fn _centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
   let popup_layout = Layout::new(
@@ -996,3 +4308,254 @@ fn centered_rect_abs(width: u16, height: u16, r: Rect) -> Rect {
 
   Rect::new(offset_x, offset_y, width, height)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn unit(name: &str, active_state: &str) -> UnitWithStatus {
+    UnitWithStatus {
+      name: name.into(),
+      scope: UnitScope::Global,
+      description: String::new(),
+      properties: None,
+      state_change_timestamp: None,
+      load_state: "loaded".into(),
+      active_state: active_state.into(),
+      sub_state: String::new(),
+    }
+  }
+
+  fn home() -> (Home, std::sync::mpsc::Receiver<LogQuery>) {
+    let (journalctl_tx, journalctl_rx) = std::sync::mpsc::channel();
+    let home = Home { journalctl_tx: Some(journalctl_tx), ..Default::default() };
+    (home, journalctl_rx)
+  }
+
+  #[test]
+  fn stateful_list_empty() {
+    let mut list = StatefulList::<u8>::default();
+    list.next();
+    assert_eq!(list.selected(), None);
+    list.previous();
+    assert_eq!(list.selected(), None);
+  }
+
+  #[test]
+  fn stateful_list_single_item() {
+    let mut list = StatefulList::with_items(vec![7]);
+    list.previous();
+    assert_eq!(list.selected(), Some(&7));
+    list.next();
+    assert_eq!(list.selected(), Some(&7));
+    list.previous();
+    assert_eq!(list.state.selected(), Some(0));
+  }
+
+  #[test]
+  fn stateful_list_wraps_around() {
+    let mut list = StatefulList::with_items(vec!['a', 'b', 'c']);
+    list.next();
+    assert_eq!(list.selected(), Some(&'a'));
+    list.previous();
+    assert_eq!(list.selected(), Some(&'c'));
+    list.next();
+    assert_eq!(list.selected(), Some(&'a'));
+    list.next();
+    list.next();
+    list.next();
+    assert_eq!(list.selected(), Some(&'a'));
+  }
+
+  #[test]
+  fn stateful_list_select_and_unselect() {
+    let mut list = StatefulList::with_items(vec!['a', 'b', 'c']);
+    list.select(Some(1));
+    assert_eq!(list.selected(), Some(&'b'));
+    list.unselect();
+    assert_eq!(list.selected(), None);
+    // starts from the top again
+    list.previous();
+    assert_eq!(list.selected(), Some(&'a'));
+  }
+
+  #[test]
+  fn get_logs_without_journalctl_thread() {
+    // before init
+    let mut uninitialized = Home::default();
+    uninitialized.set_units(vec![unit("foo.service", "active")]);
+    uninitialized.get_logs();
+
+    // after the thread exited
+    let (mut home, journalctl_rx) = home();
+    drop(journalctl_rx);
+    home.set_units(vec![unit("foo.service", "active")]);
+    home.get_logs();
+  }
+
+  #[test]
+  fn group_instances_under_template() {
+    let (mut home, _journalctl_rx) = home();
+    home.group_instances = true;
+    home.set_units(vec![
+      unit("cron.service", "active"),
+      unit("getty@tty1.service", "active"),
+      unit("getty@tty2.service", "failed"),
+      unit("user@1000.service", "active"),
+    ]);
+    let names = |home: &Home| home.filtered_units.items.iter().map(|u| u.name.clone()).collect_vec();
+    // a single instance isn't grouped
+    assert_eq!(names(&home), ["cron.service", "getty@.service", "user@1000.service"]);
+    assert_eq!(home.filtered_units.items[1].active_state, "failed");
+
+    home.select(Some(1));
+    home.run_command(Command::ActionMenu);
+    assert_eq!(
+      names(&home),
+      ["cron.service", "getty@.service", "getty@tty1.service", "getty@tty2.service", "user@1000.service"]
+    );
+
+    // collapsing from an instance selects the group again
+    home.select(Some(3));
+    home.run_command(Command::ScrollLogsLeft);
+    assert_eq!(names(&home), ["cron.service", "getty@.service", "user@1000.service"]);
+    assert_eq!(home.filtered_units.selected().unwrap().name, "getty@.service");
+  }
+
+  #[test]
+  fn set_units_updates_in_place() {
+    let (mut home, _journalctl_rx) = home();
+    let units = (0..5000).map(|i| unit(&format!("unit-{i:05}.service"), "active")).collect_vec();
+    home.set_units(units.clone());
+    home.select(Some(2500));
+    home.all_units.get_mut(&units[2500].id()).unwrap().properties = Some(UnitProperties::default());
+
+    // one unit gone, one new, one changed state
+    let mut refreshed = units.clone();
+    refreshed.remove(10);
+    refreshed.insert(20, unit("unit-00019a.service", "active"));
+    refreshed[2500].active_state = "failed".into();
+    home.set_units(refreshed.clone());
+
+    assert_eq!(home.all_units.keys().cloned().collect_vec(), refreshed.iter().map(|u| u.id()).collect_vec());
+    let selected = home.filtered_units.selected().unwrap();
+    assert_eq!(selected.name, "unit-02500.service");
+    assert_eq!(selected.active_state, "failed");
+    // loaded on demand, so it must survive the refresh
+    assert!(selected.properties.is_some());
+  }
+
+  #[test]
+  fn help_scrolls_on_small_terminals() {
+    let (mut home, _journalctl_rx) = home();
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 20)).unwrap();
+    home.dispatch(Action::ToggleHelp);
+    for _ in 0..500 {
+      home.handle_key_events(KeyCode::Char('j').into());
+    }
+    terminal.draw(|f| home.render(f, f.size())).unwrap();
+    // clamped to the last page of the help
+    let max_scroll = home.help_scroll;
+    assert!(max_scroll > 0 && max_scroll < 500);
+    home.handle_key_events(KeyCode::Up.into());
+    assert_eq!(home.help_scroll, max_scroll - 1);
+
+    // reopened at the top
+    for action in home.handle_key_events(KeyCode::Esc.into()) {
+      home.dispatch(action);
+    }
+    home.dispatch(Action::ToggleHelp);
+    assert_eq!(home.help_scroll, 0);
+  }
+
+  #[test]
+  fn log_bookmarks_with_wrapped_lines() {
+    let (mut home, _journalctl_rx) = home();
+    home.set_units(vec![unit("foo.service", "active")]);
+    home.select(Some(0));
+    let id = home.selected_service().unwrap();
+    // shown newest first: "c" takes 1 row, the long line 3 and "a" 1
+    home.log_wrap = true;
+    home.logs_viewport_width = 10;
+    home.logs = vec!["a".into(), "b".repeat(25), "c".into(), "c".into()];
+    assert_eq!(home.log_line_offsets(), vec![5, 2, 1, 0]);
+
+    // scrolled into the middle of the wrapped line
+    home.logs_scroll_offset = 3;
+    home.dispatch(Action::ToggleLogBookmark);
+    assert_eq!(home.log_bookmarks[&id], vec![1]);
+    // identical lines are bookmarked separately
+    home.logs_scroll_offset = 0;
+    home.dispatch(Action::ToggleLogBookmark);
+    assert_eq!(home.log_bookmarks[&id], vec![1, 3]);
+
+    home.dispatch(Action::NextLogBookmark);
+    assert_eq!(home.logs_scroll_offset, 2);
+    home.dispatch(Action::NextLogBookmark);
+    assert_eq!(home.logs_scroll_offset, 0);
+
+    // without wrapping every line is one row
+    home.log_wrap = false;
+    assert_eq!(home.log_line_offsets(), vec![3, 2, 1, 0]);
+  }
+
+  #[test]
+  fn jump_to_log_match_with_wrapped_lines() {
+    let (mut home, _journalctl_rx) = home();
+    home.log_wrap = true;
+    home.logs_viewport_width = 10;
+    // shown newest first: "error 2" takes 1 row, the long line 3
+    home.logs = vec!["error 1".into(), "x".repeat(25), "error 2".into()];
+    home.log_search = Input::new("error".into());
+
+    assert!(home.jump_to_log_match(true, true));
+    assert_eq!(home.logs_scroll_offset, 0);
+    assert!(home.jump_to_log_match(true, false));
+    assert_eq!(home.logs_scroll_offset, 4);
+    // wraps around
+    assert!(home.jump_to_log_match(true, false));
+    assert_eq!(home.logs_scroll_offset, 0);
+    assert!(home.jump_to_log_match(false, false));
+    assert_eq!(home.logs_scroll_offset, 4);
+
+    home.log_search = Input::new("nothing".into());
+    assert!(!home.jump_to_log_match(true, true));
+  }
+
+  #[test]
+  fn remap_log_bookmarks_after_reload() {
+    let old = ["a", "b", "c"].map(String::from);
+    // one line fell off the start, two came in
+    let new = ["b", "c", "d", "e"].map(String::from);
+    let mut bookmarks = vec![0, 2];
+    remap_log_bookmarks(&mut bookmarks, &old, &new);
+    assert_eq!(bookmarks, vec![1]);
+
+    // different logs altogether
+    let mut bookmarks = vec![1];
+    remap_log_bookmarks(&mut bookmarks, &old, &["x".to_string()]);
+    assert!(bookmarks.is_empty());
+  }
+
+  #[test]
+  fn unit_diff_prefixes() {
+    let vendor = "[Service]\nExecStart=/usr/bin/foo\nRestart=no\n";
+    let effective = "[Service]\nExecStart=/usr/bin/foo\nRestart=always\n";
+    assert_eq!(
+      unit_diff(vendor, effective),
+      vec![" [Service]", " ExecStart=/usr/bin/foo", "-Restart=no", "+Restart=always"]
+    );
+    assert_eq!(unit_diff(vendor, vendor), vec![" [Service]", " ExecStart=/usr/bin/foo", " Restart=no"]);
+  }
+
+  #[test]
+  fn strip_cat_headers_keeps_config_only() {
+    let output = "# /usr/lib/systemd/system/foo.service\n[Service]\nExecStart=/usr/bin/foo\n\n\
+                  # /etc/systemd/system/foo.service.d/override.conf\n[Service]\nRestart=always\n";
+    let paths =
+      ["/etc/systemd/system/foo.service.d/override.conf".into(), "/usr/lib/systemd/system/foo.service".into()];
+    assert_eq!(strip_cat_headers(output, &paths), "[Service]\nExecStart=/usr/bin/foo\n[Service]\nRestart=always\n");
+    // comments in the files themselves stay
+    assert_eq!(strip_cat_headers("# /not/a/known/path\n", &paths), "# /not/a/known/path\n");
+  }
+}
@@ -14,6 +14,14 @@ use crate::action::Action;
 #[derive(Default)]
 pub struct Logger {
   state: TuiWidgetState,
+  /// Leave the emoji out of the title
+  ascii: bool,
+}
+
+impl Logger {
+  pub fn new(ascii: bool) -> Self {
+    Self { ascii, ..Default::default() }
+  }
 }
 
 impl Component for Logger {
@@ -23,8 +31,9 @@ impl Component for Logger {
   }
 
   fn render(&mut self, f: &mut Frame<'_>, rect: Rect) {
+    let title = if self.ascii { " systemctl-tui logs" } else { " 📝 systemctl-tui logs" };
     let w = TuiLoggerWidget::default()
-      .block(Block::default().title(" 📝 systemctl-tui logs").borders(Borders::ALL))
+      .block(Block::default().title(title).borders(Borders::ALL))
       .style_error(Style::default().fg(Color::Red))
       .style_debug(Style::default().fg(Color::Green))
       .style_warn(Style::default().fg(Color::Yellow))
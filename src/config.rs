@@ -0,0 +1,139 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  keybindings::{self, Command, KeyList},
+  theme::Theme,
+  utils::get_config_dir,
+};
+
+/// User configuration, loaded from `config.toml` in the config directory. Every key is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+  /// Unit name patterns (`*` is a wildcard) to leave out of change detection, for units that change constantly
+  pub change_detection_exclude: Vec<String>,
+  /// What Enter does while typing in the search box
+  pub search_enter: SearchEnter,
+  /// Maximum width of the values column in the details pane. Longer values are cut off, `V` shows them in full
+  pub details_value_max_width: Option<u16>,
+  /// Extra arguments appended to every journalctl invocation. An escape hatch, nothing checks that they make sense
+  pub journalctl_extra_args: Vec<String>,
+  /// Run Stop/Restart/Disable from the action menu without asking for confirmation first
+  pub skip_confirmations: bool,
+  /// Keys for the unit list by command name, replacing that command's default keys
+  pub keybindings: HashMap<Command, KeyList>,
+  /// What happens to new log lines while following is paused
+  pub paused_logs: PausedLogs,
+  /// Stick to ASCII for titles, the spinner and status glyphs, for terminals and fonts that lack the symbols
+  pub ascii: bool,
+  /// How the unit list and the details/logs share the screen, until it's toggled with `|`
+  pub split: Split,
+  /// Colors for the UI, any role left out keeps its default
+  pub theme: Theme,
+}
+
+// Arguments that systemctl-tui sets itself; overriding them would break how logs are fetched and followed
+const MANAGED_JOURNALCTL_ARGS: &[&str] = &["-u", "--unit", "-f", "--follow", "-n", "--lines"];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchEnter {
+  /// Open the action menu for the selected unit
+  #[default]
+  ActionMenu,
+  /// Just leave the search box, keeping the filter
+  ConfirmSearch,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PausedLogs {
+  /// Keep them and add them to the logs on resume
+  #[default]
+  Buffer,
+  /// Throw them away, the logs pick up from where they are on resume
+  Drop,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Split {
+  /// The unit list on the left, details and logs on the right
+  #[default]
+  SideBySide,
+  /// The unit list on top, details and logs below it. Better for tall terminals
+  Stacked,
+}
+
+impl Split {
+  pub fn toggle(self) -> Self {
+    match self {
+      Split::SideBySide => Split::Stacked,
+      Split::Stacked => Split::SideBySide,
+    }
+  }
+}
+
+impl Config {
+  pub fn load() -> Result<Self> {
+    let path = config_file_path()?;
+    if !path.exists() {
+      return Ok(Self::default());
+    }
+
+    let contents = std::fs::read_to_string(&path).context(format!("Unable to read {path:?}"))?;
+    let config: Self = toml::from_str(&contents).context(format!("Unable to parse {path:?}"))?;
+    config.validate().context(format!("Invalid config in {path:?}"))?;
+    Ok(config)
+  }
+
+  fn validate(&self) -> Result<()> {
+    for arg in &self.journalctl_extra_args {
+      // catch `--lines=10` and `-n10` as well as the bare flags
+      let conflict = MANAGED_JOURNALCTL_ARGS.iter().find(|&&managed| match managed.len() {
+        2 => arg.starts_with(managed),
+        _ => arg == managed || arg.starts_with(&format!("{managed}=")),
+      });
+      if let Some(managed) = conflict {
+        anyhow::bail!("journalctl_extra_args can't contain `{arg}`, systemctl-tui manages `{managed}` itself");
+      }
+    }
+    keybindings::keymap(&self.keybindings)?;
+    Ok(())
+  }
+
+  pub fn is_excluded_from_change_detection(&self, unit_name: &str, short_name: &str) -> bool {
+    self.change_detection_exclude.iter().any(|p| matches_pattern(p, unit_name) || matches_pattern(p, short_name))
+  }
+}
+
+pub fn config_file_path() -> Result<PathBuf> {
+  Ok(get_config_dir()?.join("config.toml"))
+}
+
+/// Match a name against a pattern where `*` matches any number of characters
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+  let mut parts = pattern.split('*');
+  let first = parts.next().unwrap_or_default();
+  let Some(mut remaining) = name.strip_prefix(first) else {
+    return false;
+  };
+
+  let parts = parts.collect::<Vec<_>>();
+  let Some((last, middle)) = parts.split_last() else {
+    // no wildcards, must be an exact match
+    return remaining.is_empty();
+  };
+
+  for part in middle {
+    match remaining.find(part) {
+      Some(i) => remaining = &remaining[i + part.len()..],
+      None => return false,
+    }
+  }
+
+  remaining.ends_with(last)
+}
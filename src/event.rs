@@ -30,19 +30,28 @@ pub struct EventHandler {
   cancellation_token: CancellationToken,
 }
 
-const SERVICE_REFRESH_INTERVAL_MS: u64 = 5000;
-
 impl EventHandler {
-  pub fn new(home: Arc<Mutex<Home>>, action_tx: mpsc::UnboundedSender<Action>) -> Self {
+  /// `refresh_interval` is the number of seconds between unit list refreshes, 0 turns them off
+  pub fn new(home: Arc<Mutex<Home>>, action_tx: mpsc::UnboundedSender<Action>, refresh_interval: u64) -> Self {
     let (event_tx, mut event_rx) = mpsc::unbounded_channel();
     let cancellation_token = CancellationToken::new();
     let _cancellation_token = cancellation_token.clone();
     let task = tokio::spawn(async move {
       let mut reader = crossterm::event::EventStream::new();
-      let mut refresh_services_interval = tokio::time::interval(Duration::from_millis(SERVICE_REFRESH_INTERVAL_MS));
-      refresh_services_interval.tick().await;
+      let mut refresh_services_interval =
+        (refresh_interval > 0).then(|| tokio::time::interval(Duration::from_secs(refresh_interval)));
+      if let Some(interval) = &mut refresh_services_interval {
+        interval.tick().await;
+      }
       loop {
-        let refresh_delay = refresh_services_interval.tick();
+        let refresh_delay = async {
+          match &mut refresh_services_interval {
+            Some(interval) => {
+              interval.tick().await;
+            },
+            None => std::future::pending().await,
+          }
+        };
         let crossterm_event = reader.next().fuse();
         tokio::select! {
           _ = _cancellation_token.cancelled() => {
@@ -52,10 +61,11 @@ impl EventHandler {
             match maybe_event {
               Some(Ok(evt)) => {
                 match evt {
-                  CrosstermEvent::Key(key) => {
-                    if key.kind == KeyEventKind::Press {
-                      event_tx.send(Event::Key(key)).unwrap();
-                    }
+                  CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => {
+                    event_tx.send(Event::Key(key)).unwrap();
+                  },
+                  CrosstermEvent::Mouse(mouse) => {
+                    event_tx.send(Event::Mouse(mouse)).unwrap();
                   },
                   // interestingly, we never get these if running in dev mode with watchexec
                   CrosstermEvent::Resize(x, y) => {
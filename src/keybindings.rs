@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Things that can be bound to keys in the unit list, named in the config file in snake_case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+  Quit,
+  Up,
+  Down,
+  Top,
+  Bottom,
+  NextFailed,
+  ToggleFailedOnly,
+  RestartFailed,
+  /// Search the units, or the logs when they're focused
+  Search,
+  NextLogMatch,
+  PreviousLogMatch,
+  StateChanges,
+  ToggleCurrentBootLogs,
+  CycleLogPriority,
+  ViewBoots,
+  ViewUnitFiles,
+  ReloadLogs,
+  DaemonReload,
+  LoadMoreLogs,
+  ToggleDetails,
+  ToggleRecentErrors,
+  ToggleLogsFocus,
+  CopyFilterCommand,
+  ToggleAgeColumn,
+  ToggleFullNames,
+  ToggleGroupInstances,
+  ToggleFollowLogs,
+  TogglePauseLogs,
+  ViewFullDetails,
+  ViewUnitCat,
+  EditUnitFile,
+  ViewStatus,
+  ViewDependencies,
+  CopyLogs,
+  ExportLogs,
+  CycleScope,
+  CycleUnitType,
+  InvertFilter,
+  ToggleLogBookmark,
+  NextLogBookmark,
+  ToggleChildLogs,
+  ToggleLogWrap,
+  ToggleSplit,
+  /// Scroll unwrapped logs sideways, when they're focused
+  ScrollLogsLeft,
+  ScrollLogsRight,
+  ActionMenu,
+  /// Mark units to start, stop or restart together
+  ToggleMark,
+  ClearMarks,
+  CommandPalette,
+}
+
+impl Command {
+  /// What the command palette offers, in order. Moving around the list is left out
+  pub const PALETTE: &'static [(Command, &'static str)] = &[
+    (Command::Search, "Search units"),
+    (Command::NextFailed, "Go to the next failed unit"),
+    (Command::ToggleFailedOnly, "Only show failed units"),
+    (Command::RestartFailed, "Restart all failed units"),
+    (Command::ToggleRecentErrors, "Only show units that logged errors in the last hour"),
+    (Command::InvertFilter, "Invert the filter"),
+    (Command::CycleScope, "Cycle the scope (all, system, user)"),
+    (Command::CycleUnitType, "Cycle the unit type"),
+    (Command::ClearMarks, "Clear the marked units"),
+    (Command::DaemonReload, "Reload the systemd configuration (daemon-reload)"),
+    (Command::StateChanges, "View recent state changes"),
+    (Command::ViewUnitFiles, "List unit files to enable/disable"),
+    (Command::ViewFullDetails, "View the full details"),
+    (Command::ViewUnitCat, "View the unit file (systemctl cat)"),
+    (Command::EditUnitFile, "Edit the unit file (systemctl edit --full)"),
+    (Command::ViewStatus, "View the full status (systemctl status)"),
+    (Command::ViewDependencies, "View the dependencies"),
+    (Command::ToggleDetails, "Expand/collapse the details"),
+    (Command::ToggleAgeColumn, "Show/hide how long units have been in their state"),
+    (Command::ToggleFullNames, "Show full unit names, with the .service suffix"),
+    (Command::ToggleGroupInstances, "Group template instances (foo@a, foo@b...) under one row"),
+    (Command::ToggleLogsFocus, "Focus the logs/unit list"),
+    (Command::ReloadLogs, "Reload the logs"),
+    (Command::LoadMoreLogs, "Load more logs"),
+    (Command::ToggleFollowLogs, "Toggle following new logs"),
+    (Command::TogglePauseLogs, "Pause/resume new log lines"),
+    (Command::ToggleCurrentBootLogs, "Switch logs between the current boot and all boots"),
+    (Command::ViewBoots, "Pick a boot to show logs from"),
+    (Command::CycleLogPriority, "Cycle the minimum log priority"),
+    (Command::ToggleChildLogs, "Toggle logs from child processes"),
+    (Command::ToggleLogWrap, "Toggle wrapping long log lines"),
+    (Command::ToggleLogBookmark, "Toggle a bookmark on the log line at the top"),
+    (Command::NextLogBookmark, "Go to the next log bookmark"),
+    (Command::CopyLogs, "Copy the logs to the clipboard"),
+    (Command::ExportLogs, "Write the logs to a file"),
+    (Command::CopyFilterCommand, "Copy a systemctl command that lists the units shown"),
+    (Command::ToggleSplit, "Switch between the side-by-side and stacked layouts"),
+    (Command::Quit, "Quit"),
+  ];
+}
+
+const DEFAULT_KEYBINDINGS: &[(Command, &[&str])] = &[
+  (Command::Quit, &["q"]),
+  (Command::Up, &["up", "k"]),
+  (Command::Down, &["down", "j"]),
+  (Command::Top, &["g"]),
+  (Command::Bottom, &["G"]),
+  (Command::NextFailed, &["f"]),
+  (Command::ToggleFailedOnly, &["x"]),
+  (Command::RestartFailed, &["X"]),
+  (Command::Search, &["/"]),
+  (Command::NextLogMatch, &["n"]),
+  (Command::PreviousLogMatch, &["N"]),
+  (Command::StateChanges, &["T"]),
+  (Command::ToggleCurrentBootLogs, &["b"]),
+  (Command::CycleLogPriority, &["p"]),
+  (Command::ViewBoots, &["B"]),
+  (Command::ViewUnitFiles, &["U"]),
+  (Command::ReloadLogs, &["r"]),
+  (Command::DaemonReload, &["R"]),
+  (Command::LoadMoreLogs, &["L"]),
+  (Command::ToggleDetails, &["d"]),
+  (Command::ToggleRecentErrors, &["E"]),
+  (Command::ToggleLogsFocus, &["tab"]),
+  (Command::CopyFilterCommand, &["Y"]),
+  (Command::ToggleAgeColumn, &["A"]),
+  (Command::ToggleFullNames, &["u"]),
+  (Command::ToggleGroupInstances, &["i"]),
+  (Command::ToggleFollowLogs, &["F"]),
+  (Command::TogglePauseLogs, &["P"]),
+  (Command::ViewFullDetails, &["V"]),
+  (Command::ViewUnitCat, &["c"]),
+  (Command::EditUnitFile, &["e"]),
+  (Command::ViewStatus, &["s"]),
+  (Command::ViewDependencies, &["D"]),
+  (Command::CopyLogs, &["y"]),
+  (Command::ExportLogs, &["W"]),
+  (Command::CycleScope, &["S"]),
+  (Command::CycleUnitType, &["t"]),
+  (Command::InvertFilter, &["!"]),
+  (Command::ToggleLogBookmark, &["m"]),
+  (Command::NextLogBookmark, &["'"]),
+  (Command::ToggleChildLogs, &["C"]),
+  (Command::ToggleLogWrap, &["w"]),
+  (Command::ToggleSplit, &["|"]),
+  (Command::ScrollLogsLeft, &["left"]),
+  (Command::ScrollLogsRight, &["right"]),
+  (Command::ActionMenu, &["enter"]),
+  (Command::ToggleMark, &["space"]),
+  (Command::ClearMarks, &["esc"]),
+  (Command::CommandPalette, &[":", "ctrl+p"]),
+];
+
+/// One key or a list of keys, so the config can say `up = "e"` as well as `up = ["e", "up"]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+  One(String),
+  Many(Vec<String>),
+}
+
+impl KeyList {
+  fn keys(&self) -> &[String] {
+    match self {
+      KeyList::One(key) => std::slice::from_ref(key),
+      KeyList::Many(keys) => keys,
+    }
+  }
+}
+
+/// A key plus the modifiers that matter for bindings (ctrl and alt, shift is part of the character)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+  pub code: KeyCode,
+  pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for KeyBinding {
+  fn from(key: KeyEvent) -> Self {
+    Self { code: key.code, modifiers: key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT) }
+  }
+}
+
+impl KeyBinding {
+  /// Parse keys like `j`, `G`, `ctrl+r`, `alt+enter` or `pagedown`
+  pub fn parse(s: &str) -> Result<Self> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut shift = false;
+    let mut rest = s;
+    loop {
+      let lowercase = rest.to_ascii_lowercase();
+      if lowercase.starts_with("ctrl+") && rest.len() > 5 {
+        modifiers |= KeyModifiers::CONTROL;
+        rest = &rest[5..];
+      } else if lowercase.starts_with("alt+") && rest.len() > 4 {
+        modifiers |= KeyModifiers::ALT;
+        rest = &rest[4..];
+      } else if lowercase.starts_with("shift+") && rest.len() > 6 {
+        shift = true;
+        rest = &rest[6..];
+      } else {
+        break;
+      }
+    }
+
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+      (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+      (Some(c), None) => KeyCode::Char(c),
+      _ => match rest.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+          Some(n @ 1..=12) => KeyCode::F(n),
+          _ => anyhow::bail!("Unknown key `{s}`"),
+        },
+      },
+    };
+
+    Ok(Self { code, modifiers })
+  }
+}
+
+/// The unit list's key → command lookup: the defaults, with the configured commands' keys replacing theirs
+pub fn keymap(overrides: &HashMap<Command, KeyList>) -> Result<HashMap<KeyBinding, Command>> {
+  let mut keymap = HashMap::new();
+  for (command, keys) in DEFAULT_KEYBINDINGS {
+    if overrides.contains_key(command) {
+      continue;
+    }
+    for key in *keys {
+      keymap.insert(KeyBinding::parse(key).expect("default keybindings are valid"), *command);
+    }
+  }
+
+  // configured keys win over defaults bound to other commands
+  for (command, keys) in overrides {
+    for key in keys.keys() {
+      let binding = KeyBinding::parse(key).context(format!("Invalid keybinding for {command:?}"))?;
+      keymap.insert(binding, *command);
+    }
+  }
+
+  Ok(keymap)
+}
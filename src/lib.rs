@@ -1,13 +1,36 @@
+//! The TUI is built on a small library. [`systemd`] is the part that's useful on its own: listing units, reading
+//! their properties and logs, and starting/stopping/etc. them, all returning [`anyhow::Result`]s.
+//!
+//! ```no_run
+//! use systemctl_tui::systemd::{self, Scope, UnitType};
+//! use tokio_util::sync::CancellationToken;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let units = systemd::get_all_services(Scope::All, UnitType::Service, &[]).await?;
+//! for unit in units.iter().filter(|u| u.is_failed()) {
+//!   systemd::restart_service(unit.id(), CancellationToken::new()).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
 pub mod app;
 
 pub mod action;
 
 pub mod components;
 
+pub mod config;
+
 pub mod event;
 
+pub mod keybindings;
+
 pub mod terminal;
 
 pub mod utils;
 
 pub mod systemd;
+
+pub mod state;
+pub mod theme;
@@ -2,6 +2,7 @@ use anyhow::Result;
 use clap::{Parser, ValueEnum};
 use systemctl_tui::{
   app::App,
+  config::Config,
   systemd,
   utils::{initialize_logging, initialize_panic_handler, version},
 };
@@ -10,12 +11,39 @@ use systemctl_tui::{
 #[derive(Parser, Debug)]
 #[command(version = version(), about = "A simple TUI for systemd services")]
 struct Args {
+  /// Start with the unit list already filtered by this search, e.g. `systemctl-tui nginx`
+  filter: Option<String>,
   /// The scope of the services to display. Defaults to "all" normally and "global" on WSL
   #[clap(short, long)]
   scope: Option<Scope>,
   /// Enable performance tracing (in Chromium Event JSON format)
   #[clap(short, long)]
   trace: bool,
+  /// Only show these units (comma-separated). Names without a type suffix are assumed to be services
+  #[clap(long, value_delimiter = ',')]
+  units: Vec<String>,
+  /// Show the commands that actions would run instead of running them
+  #[clap(long)]
+  dry_run: bool,
+  /// Don't ask for confirmation before stopping, restarting or disabling a unit
+  #[clap(long)]
+  no_confirm: bool,
+  /// Open with this unit selected and its logs loaded. A name without a type suffix is assumed to be a service
+  #[clap(long)]
+  unit: Option<String>,
+  /// Only use ASCII in titles, the spinner and status glyphs, for terminals that can't show the symbols
+  #[clap(long)]
+  ascii: bool,
+  /// How many log lines to load when selecting a unit. Press L to load more for the current unit
+  #[clap(long, default_value_t = 500)]
+  log_lines: usize,
+  /// Manage another machine over SSH, e.g. `user@host`. Needs key-based (or agent) auth, systemd-stdio-bridge and
+  /// journalctl on the host
+  #[clap(long)]
+  host: Option<String>,
+  /// Seconds between refreshes of the unit list, to pick up changes made outside the app. 0 turns it off
+  #[clap(long, default_value_t = 5)]
+  refresh_interval: u64,
 }
 
 #[derive(Parser, Debug, ValueEnum, Clone)]
@@ -35,6 +63,10 @@ async fn main() -> Result<()> {
   let args = Args::parse();
   initialize_logging(args.trace)?;
   initialize_panic_handler();
+  systemd::set_dry_run(args.dry_run);
+  if let Some(host) = args.host {
+    systemd::set_host(host);
+  }
 
   // There's probably a nicer way to do this than defining the scope enum twice, but this is fine for now
   let scope = match args.scope {
@@ -52,7 +84,15 @@ async fn main() -> Result<()> {
     },
   };
 
-  let mut app = App::new(scope)?;
+  let with_suffix = |name: String| if name.contains('.') { name } else { format!("{name}.service") };
+  let limit_units = args.units.into_iter().map(with_suffix).collect();
+  let start_unit = args.unit.map(with_suffix);
+
+  let mut config = Config::load()?;
+  config.skip_confirmations |= args.no_confirm;
+  config.ascii |= args.ascii;
+
+  let mut app = App::new(scope, limit_units, args.log_lines, args.refresh_interval, args.filter, start_unit, config)?;
   app.run().await?;
 
   Ok(())
@@ -0,0 +1,44 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Split, utils::get_data_dir};
+
+/// Things the user has told us that should survive restarts, stored in the data directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PersistedState {
+  /// Freeform notes, keyed by unit name
+  #[serde(default)]
+  pub notes: BTreeMap<String, String>,
+  /// The layout picked with `|`, overriding the one in the config
+  #[serde(default)]
+  pub split: Option<Split>,
+}
+
+impl PersistedState {
+  pub fn load() -> Result<Self> {
+    let path = state_file_path()?;
+    if !path.exists() {
+      return Ok(Self::default());
+    }
+
+    let contents = std::fs::read_to_string(&path).context(format!("Unable to read {path:?}"))?;
+    toml::from_str(&contents).context(format!("Unable to parse {path:?}"))
+  }
+
+  pub fn save(&self) -> Result<()> {
+    let path = state_file_path()?;
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent).context(format!("{parent:?} could not be created"))?;
+    }
+
+    let contents = toml::to_string_pretty(self)?;
+    std::fs::write(&path, contents).context(format!("Unable to write {path:?}"))?;
+    Ok(())
+  }
+}
+
+fn state_file_path() -> Result<PathBuf> {
+  Ok(get_data_dir()?.join("state.toml"))
+}
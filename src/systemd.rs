@@ -1,20 +1,37 @@
 // File initially taken from https://github.com/servicer-labs/servicer/blob/master/src/utils/systemd.rs, since modified
 
+//! Listing units and acting on them (start/stop/restart/reload/kill/enable/disable/mask) goes through systemd's
+//! D-Bus API (`org.freedesktop.systemd1.Manager`) via zbus. Only read-only extras that have no convenient D-Bus
+//! equivalent shell out: `systemctl show`/`cat`/`list-dependencies`/`status` and `journalctl`.
+//! With `--host`, both go over SSH: commands through [`cmd`], D-Bus through `systemd-stdio-bridge` on the host.
+
+use std::{
+  ffi::OsStr,
+  os::fd::OwnedFd,
+  process::Stdio,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    OnceLock,
+  },
+};
+
 use anyhow::Result;
-use duct::cmd;
+use itertools::Itertools;
 use log::error;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt};
 use tokio_util::sync::CancellationToken;
 use tracing::info;
-use zbus::{proxy, zvariant, Connection};
+use zbus::{proxy, zvariant, Connection, DBusError};
 
 #[derive(Debug, Clone)]
 pub struct UnitWithStatus {
-  pub name: String,              // The primary unit name as string
-  pub scope: UnitScope,          // System or user?
-  pub description: String,       // The human readable description string
-  pub file_path: Option<String>, // The unit file path - populated later on demand
-  pub load_state: String,        // The load state (i.e. whether the unit file has been loaded successfully)
-  pub active_state: String,      // The active state (i.e. whether the unit is currently started or not)
+  pub name: String,                        // The primary unit name as string
+  pub scope: UnitScope,                    // System or user?
+  pub description: String,                 // The human readable description string
+  pub properties: Option<UnitProperties>,  // Extra properties from `systemctl show` - populated later on demand
+  pub state_change_timestamp: Option<i64>, // Unix time of the last active state change - populated later on demand
+  pub load_state: String,                  // The load state (i.e. whether the unit file has been loaded successfully)
+  pub active_state: String,                // The active state (i.e. whether the unit is currently started or not)
   pub sub_state: String, // The sub state (a more fine-grained version of the active state that is specific to the unit type, which the active state is not)
                          // We don't use any of these right now, might as well skip'em so there's less data to clone
                          // pub followed: String, // A unit that is being followed in its state by this unit, if there is any, otherwise the empty string.
@@ -30,6 +47,30 @@ pub enum UnitScope {
   User,
 }
 
+/// A single summary of a unit's load, active, and sub states, for users who don't want to think about all three
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+  /// Loaded and active
+  Healthy,
+  /// In transition (activating, deactivating, reloading, auto-restarting) or its unit file is missing
+  Degraded,
+  /// Failed, or the unit file couldn't be loaded
+  Failed,
+  /// Anything else, usually an inactive unit
+  Unknown,
+}
+
+impl Health {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Health::Healthy => "healthy",
+      Health::Degraded => "degraded",
+      Health::Failed => "failed",
+      Health::Unknown => "unknown",
+    }
+  }
+}
+
 /// Just enough info to fully identify a unit
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UnitId {
@@ -50,6 +91,21 @@ impl UnitWithStatus {
     self.load_state == "not-found"
   }
 
+  pub fn is_masked(&self) -> bool {
+    self.load_state == "masked"
+  }
+
+  pub fn health(&self) -> Health {
+    match (self.load_state.as_str(), self.active_state.as_str(), self.sub_state.as_str()) {
+      (_, "failed", _) | (_, _, "failed") | ("error", _, _) | ("bad-setting", _, _) => Health::Failed,
+      (_, "activating" | "deactivating" | "reloading", _) | (_, _, "auto-restart") | ("not-found", _, _) => {
+        Health::Degraded
+      },
+      ("loaded", "active", _) => Health::Healthy,
+      _ => Health::Unknown,
+    }
+  }
+
   pub fn is_enabled(&self) -> bool {
     self.load_state == "loaded" && self.active_state == "active"
   }
@@ -62,6 +118,17 @@ impl UnitWithStatus {
     }
   }
 
+  /// The template an instance was made from, e.g. `getty@.service` for `getty@tty1.service`
+  pub fn template(&self) -> Option<String> {
+    let (prefix, rest) = self.name.split_once('@')?;
+    let (instance, suffix) = rest.rsplit_once('.')?;
+    (!instance.is_empty()).then(|| format!("{prefix}@.{suffix}"))
+  }
+
+  pub fn file_path(&self) -> Option<&str> {
+    self.properties.as_ref().and_then(|p| p.fragment_path.as_deref())
+  }
+
   // TODO: should we have a non-allocating version of this?
   pub fn id(&self) -> UnitId {
     UnitId { name: self.name.clone(), scope: self.scope }
@@ -69,6 +136,10 @@ impl UnitWithStatus {
 
   // useful for updating without wiping out the file path
   pub fn update(&mut self, other: UnitWithStatus) {
+    // the timestamp is stale once the state changes, it gets fetched again
+    if self.active_state != other.active_state {
+      self.state_change_timestamp = None;
+    }
     self.description = other.description;
     self.load_state = other.load_state;
     self.active_state = other.active_state;
@@ -83,7 +154,16 @@ fn to_unit_status(raw_unit: RawUnit, scope: UnitScope) -> UnitWithStatus {
   let (name, description, load_state, active_state, sub_state, _followed, _path, _job_id, _job_type, _job_path) =
     raw_unit;
 
-  UnitWithStatus { name, scope, description, file_path: None, load_state, active_state, sub_state }
+  UnitWithStatus {
+    name,
+    scope,
+    description,
+    properties: None,
+    state_change_timestamp: None,
+    load_state,
+    active_state,
+    sub_state,
+  }
 }
 
 // Different from UnitScope in that this is not for 1 specific unit (i.e. it can include multiple scopes)
@@ -95,32 +175,194 @@ pub enum Scope {
   All,
 }
 
+impl Scope {
+  /// Cycles all (merged) → system → user → all
+  pub fn next(self) -> Self {
+    match self {
+      Scope::All => Scope::Global,
+      Scope::Global => Scope::User,
+      Scope::User => Scope::All,
+    }
+  }
+}
+
+/// Which kind of units to list
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum UnitType {
+  #[default]
+  Service,
+  Timer,
+  Socket,
+  Target,
+  Mount,
+  All,
+}
+
+impl UnitType {
+  /// Cycles service → timer → socket → target → mount → all → service
+  pub fn next(self) -> Self {
+    match self {
+      UnitType::Service => UnitType::Timer,
+      UnitType::Timer => UnitType::Socket,
+      UnitType::Socket => UnitType::Target,
+      UnitType::Target => UnitType::Mount,
+      UnitType::Mount => UnitType::All,
+      UnitType::All => UnitType::Service,
+    }
+  }
+
+  /// The name `systemctl --type=` uses
+  pub fn as_str(self) -> &'static str {
+    match self {
+      UnitType::Service => "service",
+      UnitType::Timer => "timer",
+      UnitType::Socket => "socket",
+      UnitType::Target => "target",
+      UnitType::Mount => "mount",
+      UnitType::All => "all",
+    }
+  }
+
+  /// Unit name pattern that selects this type, the D-Bus equivalent of `--type=`
+  pub fn pattern(self) -> String {
+    match self {
+      UnitType::All => "*".into(),
+      t => format!("*.{}", t.as_str()),
+    }
+  }
+}
+
+/// Whether the system was booted with systemd, the same check `sd_booted()` does.
+/// Always true for a remote host, failing to connect is reported when loading units
+pub fn is_running() -> bool {
+  host().is_some() || std::path::Path::new("/run/systemd/system").exists()
+}
+
+static HOST: OnceLock<String> = OnceLock::new();
+
+/// Manage another machine over SSH instead of this one. `host` is anything `ssh` accepts, e.g. `user@host`.
+/// Set it once at startup, before anything else here runs
+pub fn set_host(host: String) {
+  let _ = HOST.set(host);
+}
+
+pub fn host() -> Option<&'static str> {
+  HOST.get().map(String::as_str)
+}
+
+/// A `systemctl`/`journalctl` command, run on the host over SSH if there is one.
+/// Environment variables set on the expression only apply locally
+pub fn cmd<U>(program: &str, args: U) -> duct::Expression
+where
+  U: IntoIterator,
+  U::Item: AsRef<OsStr>,
+{
+  let (program, args) = for_host(program, to_strings(args), false);
+  duct::cmd(program, args)
+}
+
+/// [`cmd`] for running asynchronously
+pub fn tokio_cmd<U>(program: &str, args: U) -> tokio::process::Command
+where
+  U: IntoIterator,
+  U::Item: AsRef<OsStr>,
+{
+  let (program, args) = for_host(program, to_strings(args), false);
+  let mut command = tokio::process::Command::new(program);
+  command.args(args);
+  command
+}
+
+/// The program and args to run something that takes over the terminal (an editor, a pager...), on the host if
+/// there is one. SSH gets a TTY for it and may ask for a password
+pub fn terminal_cmd(program: String, args: Vec<String>) -> (String, Vec<String>) {
+  for_host(&program, args, true)
+}
+
+fn to_strings<U>(args: U) -> Vec<String>
+where
+  U: IntoIterator,
+  U::Item: AsRef<OsStr>,
+{
+  args.into_iter().map(|a| a.as_ref().to_string_lossy().into_owned()).collect()
+}
+
+fn for_host(program: &str, args: Vec<String>, interactive: bool) -> (String, Vec<String>) {
+  let Some(host) = host() else { return (program.into(), args) };
+  // ssh joins the command into one string for the remote shell, so each arg needs quoting
+  let remote = std::iter::once(program.to_string()).chain(args).map(|a| shell_quote(&a)).join(" ");
+  let mut ssh_args = ssh_args(host, interactive);
+  ssh_args.push(remote);
+  ("ssh".into(), ssh_args)
+}
+
+fn ssh_args(host: &str, interactive: bool) -> Vec<String> {
+  let mut args = vec!["-x".to_string()];
+  if interactive {
+    args.push("-t".into());
+  } else {
+    // a password prompt would draw over the TUI and hang the command, fail instead
+    args.extend(["-T".into(), "-o".into(), "BatchMode=yes".into()]);
+  }
+  args.extend(["--".into(), host.into()]);
+  args
+}
+
+// Quote an arg for a POSIX shell, leaving ones that are obviously safe (most unit names and flags) readable
+fn shell_quote(arg: &str) -> String {
+  let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:@,+%".contains(c);
+  if !arg.is_empty() && arg.chars().all(safe) {
+    arg.to_string()
+  } else {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+  }
+}
+
+/// Why failing to reach the user's systemd instance is expected, if it is. It should always be reachable, but
+/// not when running as root (e.g. with sudo), or on a remote host where the SSH user has no session bus
+pub fn user_scope_unavailable_reason() -> Option<&'static str> {
+  if nix::unistd::geteuid().is_root() {
+    Some("we're running as root")
+  } else if host().is_some() {
+    Some("we're connected to a remote host")
+  } else {
+    None
+  }
+}
+
+/// Units of the given scope(s) and type, sorted by name.
+/// When running as root, failing to get user units isn't an error.
+/// If `limit_units` is non-empty, only units with those names are loaded and `unit_type` is ignored
 // this takes like 5-10 ms on 13th gen Intel i7 (scope=all)
-pub async fn get_all_services(scope: Scope) -> Result<Vec<UnitWithStatus>> {
+pub async fn get_all_services(
+  scope: Scope,
+  unit_type: UnitType,
+  limit_units: &[String],
+) -> Result<Vec<UnitWithStatus>> {
   let start = std::time::Instant::now();
 
   let mut units = vec![];
 
-  let is_root = nix::unistd::geteuid().is_root();
-
   match scope {
     Scope::Global => {
-      let system_units = get_services(UnitScope::Global).await?;
+      let system_units = get_services(UnitScope::Global, unit_type, limit_units).await?;
       units.extend(system_units);
     },
     Scope::User => {
-      let user_units = get_services(UnitScope::User).await?;
+      let user_units = get_services(UnitScope::User, unit_type, limit_units).await?;
       units.extend(user_units);
     },
     Scope::All => {
-      let (system_units, user_units) = tokio::join!(get_services(UnitScope::Global), get_services(UnitScope::User));
+      let (system_units, user_units) = tokio::join!(
+        get_services(UnitScope::Global, unit_type, limit_units),
+        get_services(UnitScope::User, unit_type, limit_units)
+      );
       units.extend(system_units?);
 
-      // Should always be able to get user units, but it may fail when running as root
       if let Ok(user_units) = user_units {
         units.extend(user_units);
-      } else if is_root {
-        error!("Failed to get user units, ignoring because we're running as root")
+      } else if let Some(reason) = user_scope_unavailable_reason() {
+        error!("Failed to get user units, ignoring because {reason}")
       } else {
         user_units?;
       }
@@ -128,38 +370,293 @@ pub async fn get_all_services(scope: Scope) -> Result<Vec<UnitWithStatus>> {
   }
 
   // sort by name case-insensitive
-  units.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+  units.sort_by_key(|a| a.name.to_lowercase());
 
   info!("Loaded systemd services in {:?}", start.elapsed());
 
   Ok(units)
 }
 
-async fn get_services(scope: UnitScope) -> Result<Vec<UnitWithStatus>, anyhow::Error> {
+/// Units of one scope, without the sorting and root handling of [`get_all_services`].
+/// `limit_units` works the same way
+pub async fn get_services(
+  scope: UnitScope,
+  unit_type: UnitType,
+  limit_units: &[String],
+) -> Result<Vec<UnitWithStatus>, anyhow::Error> {
   let connection = get_connection(scope).await?;
   let manager_proxy = ManagerProxy::new(&connection).await?;
-  let units = manager_proxy.list_units_by_patterns(vec![], vec!["*.service".into()]).await?;
+  let patterns = if limit_units.is_empty() { vec![unit_type.pattern()] } else { limit_units.to_vec() };
+  let units = manager_proxy.list_units_by_patterns(vec![], patterns).await?;
   let units: Vec<_> = units.into_iter().map(|u| to_unit_status(u, scope)).collect();
   Ok(units)
 }
 
-pub fn get_unit_file_location(service: &UnitId) -> Result<String> {
-  // show -P FragmentPath reitunes.service
-  let mut args = vec!["--quiet", "show", "-P", "FragmentPath"];
+/// Unit properties that aren't included in ListUnits, loaded on demand from `systemctl show`.
+/// Different systemd versions don't all emit the same properties, so anything missing or empty is `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitProperties {
+  pub fragment_path: Option<String>,      // The unit file path
+  pub invocation_id: Option<String>,      // The ID of the unit's current run
+  pub drop_in_paths: Vec<String>,         // Drop-in override files, in the order systemd applies them
+  pub control_group: Option<String>,      // The unit's cgroup path, e.g. /system.slice/foo.service
+  pub main_pid: Option<u32>,              // Only set while the unit has a main process
+  pub environment: Vec<(String, String)>, // Variables from Environment=, not including EnvironmentFile=
+  pub exec_start_pre: Vec<String>,        // Command lines, in the order systemd runs them
+  pub exec_start: Vec<String>,
+  pub exec_stop: Vec<String>,
+  pub watchdog_usec: Option<String>, // Watchdog timeout as systemctl formats it, e.g. "30s". None if the watchdog is off
+  pub result: Option<String>,        // Why the unit last stopped, e.g. "success" or "watchdog"
+  pub memory_current: Option<u64>,   // Bytes, None if memory accounting is off or the unit isn't running
+  pub cpu_usage_nsec: Option<u64>,   // Nanoseconds, None if CPU accounting is off or the unit isn't running
+  pub unit_file_state: Option<String>, // Whether the unit is enabled: "enabled", "disabled", "static", "masked"...
+  pub active_enter_timestamp: Option<i64>, // Unix time the unit last became active, None if it never did
+}
+
+const UNIT_PROPERTIES: &[&str] = &[
+  "FragmentPath",
+  "InvocationID",
+  "DropInPaths",
+  "ControlGroup",
+  "MainPID",
+  "Environment",
+  "ExecStartPre",
+  "ExecStart",
+  "ExecStop",
+  "WatchdogUSec",
+  "Result",
+  "MemoryCurrent",
+  "CPUUsageNSec",
+  "UnitFileState",
+  "ActiveEnterTimestamp",
+];
+
+/// The [`UnitProperties`] of a unit, from `systemctl show`
+pub fn get_unit_properties(service: &UnitId) -> Result<UnitProperties> {
+  // show -p FragmentPath -p InvocationID reitunes.service
+  let mut args = vec!["--quiet", "show"];
+  for &property in UNIT_PROPERTIES {
+    args.push("-p");
+    args.push(property);
+  }
   args.push(&service.name);
 
   if service.scope == UnitScope::User {
     args.insert(0, "--user");
   }
 
+  match systemctl_utc(args).read() {
+    Ok(output) => Ok(parse_unit_properties(&output)),
+    Err(e) => anyhow::bail!("Failed to get unit properties: {}", e),
+  }
+}
+
+/// The unit's effective configuration as printed by `systemctl cat`: the unit file followed by its drop-ins,
+/// each preceded by a `# /path/to/file` comment
+pub fn cat_unit(service: &UnitId) -> Result<String> {
+  let mut args = vec!["cat", "--no-pager"];
+  if service.scope == UnitScope::User {
+    args.push("--user");
+  }
+  args.push(&service.name);
+
   match cmd("systemctl", args).read() {
-    Ok(output) => Ok(output.trim().to_string()),
-    Err(e) => anyhow::bail!("Failed to get unit file location: {}", e),
+    Ok(output) => Ok(output),
+    Err(e) => anyhow::bail!("Failed to cat unit {}: {}", service.name, e),
+  }
+}
+
+/// A file on the machine being managed, e.g. a unit file from [`UnitProperties::fragment_path`]
+pub fn read_file(path: &str) -> Result<String> {
+  match cmd("cat", ["--", path]).stderr_null().read() {
+    Ok(output) => Ok(output),
+    Err(e) => anyhow::bail!("Failed to read {}: {}", path, e),
+  }
+}
+
+/// The contents of the vendor unit file that a unit file in /etc replaces, if there is one
+pub fn read_vendor_unit_file(service: &UnitId, fragment_path: &str) -> Option<String> {
+  if !fragment_path.starts_with("/etc/") {
+    return None;
+  }
+  let file_name = std::path::Path::new(fragment_path).file_name()?.to_str()?;
+  let dirs: &[&str] = match service.scope {
+    UnitScope::Global => &["/usr/lib/systemd/system", "/lib/systemd/system"],
+    UnitScope::User => &["/usr/lib/systemd/user"],
+  };
+  dirs.iter().find_map(|dir| read_file(&format!("{dir}/{file_name}")).ok())
+}
+
+/// `systemctl status` for a unit, with the colors systemctl would use in a terminal
+pub fn get_status(service: &UnitId) -> Result<String> {
+  let mut args = vec!["status", "--no-pager", "--full"];
+  if service.scope == UnitScope::User {
+    args.push("--user");
+  }
+  args.push(&service.name);
+
+  // status exits with a non-zero code for inactive and failed units, that's fine here
+  match cmd("systemctl", args).env("SYSTEMD_COLORS", "1").unchecked().read() {
+    Ok(output) => Ok(output),
+    Err(e) => anyhow::bail!("Failed to get the status of {}: {}", service.name, e),
+  }
+}
+
+/// Parse `Key=Value` lines from `systemctl show`. Unknown keys and malformed lines are skipped
+pub fn parse_unit_properties(output: &str) -> UnitProperties {
+  let mut properties = UnitProperties::default();
+
+  for line in output.lines() {
+    let Some((key, value)) = line.split_once('=') else {
+      continue;
+    };
+    let value = value.trim();
+    if value.is_empty() {
+      continue;
+    }
+
+    match key {
+      "FragmentPath" => properties.fragment_path = Some(value.to_string()),
+      "InvocationID" => properties.invocation_id = Some(value.to_string()),
+      "DropInPaths" => properties.drop_in_paths = value.split_whitespace().map(String::from).collect(),
+      "ControlGroup" => properties.control_group = Some(value.to_string()),
+      "MainPID" => properties.main_pid = value.parse().ok().filter(|pid| *pid != 0),
+      "Environment" => {
+        properties.environment =
+          split_quoted(value).into_iter().filter_map(|e| e.split_once('=').map(|(k, v)| (k.into(), v.into()))).collect()
+      },
+      "ExecStartPre" => properties.exec_start_pre = parse_exec_commands(value),
+      "ExecStart" => properties.exec_start = parse_exec_commands(value),
+      "ExecStop" => properties.exec_stop = parse_exec_commands(value),
+      "WatchdogUSec" if value != "0" && value != "infinity" => properties.watchdog_usec = Some(value.to_string()),
+      "Result" => properties.result = Some(value.to_string()),
+      // u64::MAX (or "[not set]" on newer versions) means there's no value
+      "MemoryCurrent" => properties.memory_current = value.parse().ok().filter(|v| *v != u64::MAX),
+      "CPUUsageNSec" => properties.cpu_usage_nsec = value.parse().ok().filter(|v| *v != u64::MAX),
+      "UnitFileState" => properties.unit_file_state = Some(value.to_string()),
+      "ActiveEnterTimestamp" => properties.active_enter_timestamp = parse_timestamp(value),
+      _ => {},
+    }
+  }
+
+  properties
+}
+
+// Exec* properties look like `{ path=/usr/bin/foo ; argv[]=/usr/bin/foo -x ; ignore_errors=no ; ... }`,
+// with one such block per command. We only care about the command lines
+fn parse_exec_commands(value: &str) -> Vec<String> {
+  value.split(" ; ").filter_map(|part| part.strip_prefix("argv[]=")).map(String::from).collect()
+}
+
+// Split a space-separated list where entries containing spaces are double quoted (as `systemctl show` prints them),
+// e.g. `A=1 "B=x y"` → [`A=1`, `B=x y`]
+fn split_quoted(value: &str) -> Vec<String> {
+  let mut entries = vec![];
+  let mut current = String::new();
+  let mut in_quotes = false;
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => current.extend(chars.next()),
+      '"' => in_quotes = !in_quotes,
+      c if c.is_whitespace() && !in_quotes => {
+        if !current.is_empty() {
+          entries.push(std::mem::take(&mut current));
+        }
+      },
+      c => current.push(c),
+    }
+  }
+  if !current.is_empty() {
+    entries.push(current);
   }
+  entries
 }
 
-pub async fn start_service(service: UnitId, cancel_token: CancellationToken) -> Result<()> {
-  async fn start_service(service: UnitId) -> Result<()> {
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// In dry-run mode, service actions only log the command they would have run
+pub fn set_dry_run(enabled: bool) {
+  DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+  DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// The `systemctl` command equivalent to an action, e.g. `systemctl --user restart foo.service`
+pub fn systemctl_command(verb: &str, service: &UnitId) -> String {
+  match service.scope {
+    UnitScope::Global => format!("systemctl {} {}", verb, service.name),
+    UnitScope::User => format!("systemctl --user {} {}", verb, service.name),
+  }
+}
+
+// Returns true if the action should be skipped because we're in dry-run mode
+fn skip_for_dry_run(verb: &str, service: &UnitId) -> bool {
+  if is_dry_run() {
+    info!("Dry run, not running: {}", systemctl_command(verb, service));
+  }
+  is_dry_run()
+}
+
+/// Why a unit action failed, classified by the D-Bus error name rather than the (localized) message
+#[derive(Debug)]
+pub enum SystemdError {
+  /// Usually means we need root: polkit said no, or there was no authentication agent to ask the user
+  PermissionDenied(String),
+  UnitNotFound(String),
+  /// systemd refused to queue the job, e.g. because the unit is masked or can't be reloaded
+  JobFailed(String),
+  Cancelled,
+  Other(zbus::Error),
+}
+
+impl std::fmt::Display for SystemdError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SystemdError::PermissionDenied(message) => write!(f, "Permission denied: {message}"),
+      SystemdError::UnitNotFound(message) => write!(f, "Unit not found: {message}"),
+      SystemdError::JobFailed(message) => write!(f, "{message}"),
+      SystemdError::Cancelled => write!(f, "cancelled"),
+      SystemdError::Other(e) => write!(f, "{e}"),
+    }
+  }
+}
+
+impl std::error::Error for SystemdError {}
+
+impl From<zbus::Error> for SystemdError {
+  fn from(e: zbus::Error) -> Self {
+    let (name, message) = match &e {
+      zbus::Error::MethodError(name, detail, _) => {
+        (name.to_string(), detail.clone().unwrap_or_else(|| name.to_string()))
+      },
+      zbus::Error::FDO(fdo) => (fdo.name().to_string(), fdo.description().unwrap_or_default().to_string()),
+      _ => return SystemdError::Other(e),
+    };
+
+    match name.as_str() {
+      "org.freedesktop.DBus.Error.AccessDenied" | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired" => {
+        SystemdError::PermissionDenied(message)
+      },
+      "org.freedesktop.systemd1.NoSuchUnit" | "org.freedesktop.systemd1.LoadFailed" => {
+        SystemdError::UnitNotFound(message)
+      },
+      name if name.starts_with("org.freedesktop.systemd1.") => SystemdError::JobFailed(message),
+      _ => SystemdError::Other(e),
+    }
+  }
+}
+
+/// Start a unit, like `systemctl start`.
+/// Returns once systemd has queued the job, or with an error if `cancel_token` fires
+pub async fn start_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("start", &service) {
+    return Ok(());
+  }
+
+  async fn start_service(service: UnitId) -> Result<(), SystemdError> {
     let connection = get_connection(service.scope).await?;
     let manager_proxy = ManagerProxy::new(&connection).await?;
     manager_proxy.start_unit(service.name.clone(), "replace".into()).await?;
@@ -169,8 +666,7 @@ pub async fn start_service(service: UnitId, cancel_token: CancellationToken) ->
   // god these select macros are ugly, is there really no better way to select?
   tokio::select! {
     _ = cancel_token.cancelled() => {
-        // The token was cancelled
-        anyhow::bail!("cancelled");
+        Err(SystemdError::Cancelled)
     }
     result = start_service(service) => {
         result
@@ -178,8 +674,13 @@ pub async fn start_service(service: UnitId, cancel_token: CancellationToken) ->
   }
 }
 
-pub async fn stop_service(service: UnitId, cancel_token: CancellationToken) -> Result<()> {
-  async fn stop_service(service: UnitId) -> Result<()> {
+/// Stop a unit, like `systemctl stop`. Cancellable the same way as [`start_service`]
+pub async fn stop_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("stop", &service) {
+    return Ok(());
+  }
+
+  async fn stop_service(service: UnitId) -> Result<(), SystemdError> {
     let connection = get_connection(service.scope).await?;
     let manager_proxy = ManagerProxy::new(&connection).await?;
     manager_proxy.stop_unit(service.name, "replace".into()).await?;
@@ -189,8 +690,7 @@ pub async fn stop_service(service: UnitId, cancel_token: CancellationToken) -> R
   // god these select macros are ugly, is there really no better way to select?
   tokio::select! {
     _ = cancel_token.cancelled() => {
-        // The token was cancelled
-        anyhow::bail!("cancelled");
+        Err(SystemdError::Cancelled)
     }
     result = stop_service(service) => {
         result
@@ -198,15 +698,109 @@ pub async fn stop_service(service: UnitId, cancel_token: CancellationToken) -> R
   }
 }
 
-async fn get_connection(scope: UnitScope) -> Result<Connection, anyhow::Error> {
+async fn get_connection(scope: UnitScope) -> zbus::Result<Connection> {
+  if let Some(host) = host() {
+    return remote_connection(host, scope).await;
+  }
   match scope {
-    UnitScope::Global => Ok(Connection::system().await?),
-    UnitScope::User => Ok(Connection::session().await?),
+    UnitScope::Global => Connection::system().await,
+    UnitScope::User => Connection::session().await,
+  }
+}
+
+// The host's bus through `ssh host systemd-stdio-bridge`, the same way `systemctl --host` does it.
+// One connection per scope is kept for the whole session, logging in over SSH for every call would be slow
+async fn remote_connection(host: &'static str, scope: UnitScope) -> zbus::Result<Connection> {
+  static SYSTEM: tokio::sync::OnceCell<Connection> = tokio::sync::OnceCell::const_new();
+  static SESSION: tokio::sync::OnceCell<Connection> = tokio::sync::OnceCell::const_new();
+
+  let cell = match scope {
+    UnitScope::Global => &SYSTEM,
+    UnitScope::User => &SESSION,
+  };
+  let connection = cell.get_or_try_init(|| connect_over_ssh(host, scope)).await?;
+  Ok(connection.clone())
+}
+
+async fn connect_over_ssh(host: &'static str, scope: UnitScope) -> zbus::Result<Connection> {
+  let (ours, theirs) = std::os::unix::net::UnixStream::pair()?;
+  let mut remote = "systemd-stdio-bridge".to_string();
+  if scope == UnitScope::User {
+    remote.push_str(" --user");
+  }
+  let mut child = tokio::process::Command::new("ssh")
+    .args(ssh_args(host, false))
+    .arg(remote)
+    .stdin(Stdio::from(OwnedFd::from(theirs.try_clone()?)))
+    .stdout(Stdio::from(OwnedFd::from(theirs)))
+    .stderr(Stdio::piped())
+    .spawn()?;
+  let mut stderr = child.stderr.take().expect("stderr is piped");
+
+  ours.set_nonblocking(true)?;
+  match zbus::connection::Builder::unix_stream(tokio::net::UnixStream::from_std(ours)?).build().await {
+    Ok(connection) => {
+      // ssh exits when the connection is dropped, at the latest when we do
+      tokio::spawn(async move {
+        let mut lines = tokio::io::BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+          info!("ssh {host}: {line}");
+        }
+      });
+      Ok(connection)
+    },
+    Err(e) => {
+      // usually ssh couldn't connect or log in, and said why on stderr
+      let _ = child.kill().await;
+      let mut output = String::new();
+      let _ = stderr.read_to_string(&mut output).await;
+      match output.trim() {
+        "" => Err(zbus::Error::Failure(format!("Failed to connect to {host}: {e}"))),
+        output => Err(zbus::Error::Failure(format!("Failed to connect to {host}: {output}"))),
+      }
+    },
   }
 }
 
-pub async fn restart_service(service: UnitId, cancel_token: CancellationToken) -> Result<()> {
-  async fn restart(service: UnitId) -> Result<()> {
+/// An installed unit file, which may or may not be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitFile {
+  pub name: String,
+  pub scope: UnitScope,
+  /// enabled, disabled, static, masked...
+  pub state: String,
+}
+
+impl UnitFile {
+  pub fn id(&self) -> UnitId {
+    UnitId { name: self.name.clone(), scope: self.scope }
+  }
+}
+
+/// Installed unit files matching the type, like `systemctl list-unit-files --type=`
+pub async fn get_unit_files(scope: UnitScope, unit_type: UnitType) -> Result<Vec<UnitFile>> {
+  let connection = get_connection(scope).await?;
+  let manager_proxy = ManagerProxy::new(&connection).await?;
+  let files = manager_proxy.list_unit_files_by_patterns(vec![], vec![unit_type.pattern()]).await?;
+
+  let mut unit_files = files
+    .into_iter()
+    .filter_map(|(path, state)| {
+      let name = std::path::Path::new(&path).file_name()?.to_str()?.to_string();
+      Some(UnitFile { name, scope, state })
+    })
+    .collect::<Vec<_>>();
+  unit_files.sort_by(|a, b| a.name.cmp(&b.name));
+  Ok(unit_files)
+}
+
+/// Restart a unit, like `systemctl restart`. Cancellable the same way as [`start_service`]
+pub async fn restart_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("restart", &service) {
+    return Ok(());
+  }
+
+  async fn restart(service: UnitId) -> Result<(), SystemdError> {
     let connection = get_connection(service.scope).await?;
     let manager_proxy = ManagerProxy::new(&connection).await?;
     manager_proxy.restart_unit(service.name, "replace".into()).await?;
@@ -216,8 +810,7 @@ pub async fn restart_service(service: UnitId, cancel_token: CancellationToken) -
   // god these select macros are ugly, is there really no better way to select?
   tokio::select! {
     _ = cancel_token.cancelled() => {
-        // The token was cancelled
-        anyhow::bail!("cancelled");
+        Err(SystemdError::Cancelled)
     }
     result = restart(service) => {
         result
@@ -225,6 +818,424 @@ pub async fn restart_service(service: UnitId, cancel_token: CancellationToken) -
   }
 }
 
+/// Signals that can be sent to a unit's processes with [`kill_service`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+  Term,
+  Kill,
+  Hup,
+  Usr1,
+  Usr2,
+}
+
+impl Signal {
+  pub const ALL: [Signal; 5] = [Signal::Term, Signal::Kill, Signal::Hup, Signal::Usr1, Signal::Usr2];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Signal::Term => "SIGTERM",
+      Signal::Kill => "SIGKILL",
+      Signal::Hup => "SIGHUP",
+      Signal::Usr1 => "SIGUSR1",
+      Signal::Usr2 => "SIGUSR2",
+    }
+  }
+
+  fn number(&self) -> i32 {
+    match self {
+      Signal::Term => libc::SIGTERM,
+      Signal::Kill => libc::SIGKILL,
+      Signal::Hup => libc::SIGHUP,
+      Signal::Usr1 => libc::SIGUSR1,
+      Signal::Usr2 => libc::SIGUSR2,
+    }
+  }
+}
+
+/// The `systemctl` arguments before the unit name that [`kill_service`] is equivalent to
+pub fn kill_verb(signal: Signal) -> String {
+  format!("kill --signal={}", signal.name())
+}
+
+/// Send a signal to all of a unit's processes, like `systemctl kill --signal=<signal>`.
+/// Cancellable the same way as [`start_service`]
+pub async fn kill_service(
+  service: UnitId,
+  signal: Signal,
+  cancel_token: CancellationToken,
+) -> Result<(), SystemdError> {
+  if skip_for_dry_run(&kill_verb(signal), &service) {
+    return Ok(());
+  }
+
+  async fn kill(service: UnitId, signal: Signal) -> Result<(), SystemdError> {
+    let connection = get_connection(service.scope).await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.kill_unit(service.name, "all".into(), signal.number()).await?;
+    Ok(())
+  }
+
+  tokio::select! {
+    _ = cancel_token.cancelled() => {
+        Err(SystemdError::Cancelled)
+    }
+    result = kill(service, signal) => {
+        result
+    }
+  }
+}
+
+/// The log tail that `systemctl status` prints below the unit's status, for when journalctl can't be used
+pub fn get_status_log_tail(service: &UnitId, lines: usize) -> Result<Vec<String>> {
+  let mut args = vec!["status".to_string(), "--no-pager".into(), "--full".into(), format!("--lines={lines}")];
+  if service.scope == UnitScope::User {
+    args.push("--user".into());
+  }
+  args.push(service.name.clone());
+
+  // status exits with a non-zero code for inactive and failed units, that's fine here
+  let output = cmd("systemctl", args).unchecked().read()?;
+  // the logs come after the first blank line
+  Ok(output.split_once("\n\n").map(|(_, logs)| logs.lines().map(String::from).collect()).unwrap_or_default())
+}
+
+/// A boot recorded in the journal, from `journalctl --list-boots`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Boot {
+  /// Relative to the current boot: 0 is the current boot, -1 the one before...
+  pub offset: i32,
+  pub id: String,
+  pub first_entry: String,
+  pub last_entry: String,
+  /// How long the system was up (or has been up, for the current boot) according to the journal
+  pub duration: Option<chrono::Duration>,
+}
+
+/// The boots recorded in the journal, oldest first, from `journalctl --list-boots`
+pub fn list_boots() -> Result<Vec<Boot>> {
+  let output = cmd("journalctl", ["--list-boots", "--no-pager", "--quiet"]).read()?;
+  Ok(parse_boots(&output))
+}
+
+// Lines look like ` -1 8e1d4b2c... Thu 2024-01-04 09:12:01 UTC Thu 2024-01-04 17:30:44 UTC`.
+// Newer versions of journalctl print a header line first, which is skipped because it doesn't start with a number
+fn parse_boots(output: &str) -> Vec<Boot> {
+  output
+    .lines()
+    .filter_map(|line| {
+      let parts = line.split_whitespace().collect::<Vec<_>>();
+      let offset = parts.first()?.parse().ok()?;
+      let id = parts.get(1)?.to_string();
+      // the rest is two timestamps of the form `weekday date time timezone`
+      let timestamps = parts.get(2..)?;
+      if timestamps.len() != 8 {
+        return None;
+      }
+      let (first, last) = timestamps.split_at(4);
+
+      // both timestamps use the same timezone, so it can be ignored for the duration
+      let parse =
+        |t: &[&str]| chrono::NaiveDateTime::parse_from_str(&format!("{} {}", t[1], t[2]), "%Y-%m-%d %H:%M:%S");
+      let duration = match (parse(first), parse(last)) {
+        (Ok(first), Ok(last)) => Some(last - first),
+        _ => None,
+      };
+
+      Some(Boot { offset, id, first_entry: first.join(" "), last_entry: last.join(" "), duration })
+    })
+    .collect()
+}
+
+/// When each of the given units last changed its active state (`StateChangeTimestamp`), as unix time.
+/// Units that systemd has no timestamp for (e.g. never started) are left out
+pub fn get_state_change_timestamps(scope: UnitScope, names: &[String]) -> Result<Vec<(String, i64)>> {
+  if names.is_empty() {
+    return Ok(vec![]);
+  }
+
+  let mut args = vec!["show", "-p", "Id", "-p", "StateChangeTimestamp"];
+  if scope == UnitScope::User {
+    args.push("--user");
+  }
+  args.extend(names.iter().map(String::as_str));
+
+  let output = systemctl_utc(args).read()?;
+  Ok(parse_state_change_timestamps(&output))
+}
+
+/// `systemctl` with timestamps printed in UTC, for [`parse_timestamp`]. `--timestamp=unix` would be simpler,
+/// but it needs systemd 248. Goes through `env` so this also works over SSH
+fn systemctl_utc(args: Vec<&str>) -> duct::Expression {
+  cmd("env", ["TZ=UTC", "systemctl"].into_iter().chain(args))
+}
+
+// Timestamps look like `Tue 2024-05-14 10:22:33 UTC`, and are empty, "n/a" or "0" if there's none
+fn parse_timestamp(value: &str) -> Option<i64> {
+  let value = value.strip_suffix(" UTC")?;
+  chrono::NaiveDateTime::parse_from_str(value, "%a %Y-%m-%d %H:%M:%S").ok().map(|t| t.and_utc().timestamp())
+}
+
+// `systemctl show` with several units prints one block of properties per unit, separated by blank lines
+fn parse_state_change_timestamps(output: &str) -> Vec<(String, i64)> {
+  output
+    .split("\n\n")
+    .filter_map(|block| {
+      let mut id = None;
+      let mut timestamp = None;
+      for line in block.lines() {
+        match line.split_once('=') {
+          Some(("Id", value)) => id = Some(value.to_string()),
+          Some(("StateChangeTimestamp", value)) => timestamp = parse_timestamp(value),
+          _ => {},
+        }
+      }
+      Some((id?, timestamp?))
+    })
+    .collect()
+}
+
+/// Reload a unit's configuration, like `systemctl reload`. Cancellable the same way as [`start_service`]
+pub async fn reload_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("reload", &service) {
+    return Ok(());
+  }
+
+  async fn reload(service: UnitId) -> Result<(), SystemdError> {
+    let connection = get_connection(service.scope).await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.reload_unit(service.name, "replace".into()).await?;
+    Ok(())
+  }
+
+  tokio::select! {
+    _ = cancel_token.cancelled() => {
+        Err(SystemdError::Cancelled)
+    }
+    result = reload(service) => {
+        result
+    }
+  }
+}
+
+/// Enable a unit, like `systemctl enable`. Cancellable the same way as [`start_service`]
+pub async fn enable_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("enable", &service) {
+    return Ok(());
+  }
+
+  async fn enable(service: UnitId) -> Result<(), SystemdError> {
+    let connection = get_connection(service.scope).await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.enable_unit_files(vec![service.name], false, true).await?;
+    // like `systemctl enable`, reload so the manager picks up the new symlinks
+    manager_proxy.reload().await?;
+    Ok(())
+  }
+
+  tokio::select! {
+    _ = cancel_token.cancelled() => {
+        Err(SystemdError::Cancelled)
+    }
+    result = enable(service) => {
+        result
+    }
+  }
+}
+
+/// Disable a unit, like `systemctl disable`. Cancellable the same way as [`start_service`]
+pub async fn disable_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("disable", &service) {
+    return Ok(());
+  }
+
+  async fn disable(service: UnitId) -> Result<(), SystemdError> {
+    let connection = get_connection(service.scope).await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.disable_unit_files(vec![service.name], false).await?;
+    manager_proxy.reload().await?;
+    Ok(())
+  }
+
+  tokio::select! {
+    _ = cancel_token.cancelled() => {
+        Err(SystemdError::Cancelled)
+    }
+    result = disable(service) => {
+        result
+    }
+  }
+}
+
+/// Mask a unit so it can't be started at all, like `systemctl mask`. Cancellable the same way as [`start_service`]
+pub async fn mask_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("mask", &service) {
+    return Ok(());
+  }
+
+  async fn mask(service: UnitId) -> Result<(), SystemdError> {
+    let connection = get_connection(service.scope).await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.mask_unit_files(vec![service.name], false, false).await?;
+    manager_proxy.reload().await?;
+    Ok(())
+  }
+
+  tokio::select! {
+    _ = cancel_token.cancelled() => {
+        Err(SystemdError::Cancelled)
+    }
+    result = mask(service) => {
+        result
+    }
+  }
+}
+
+/// Unmask a unit, like `systemctl unmask`. Cancellable the same way as [`start_service`]
+pub async fn unmask_service(service: UnitId, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if skip_for_dry_run("unmask", &service) {
+    return Ok(());
+  }
+
+  async fn unmask(service: UnitId) -> Result<(), SystemdError> {
+    let connection = get_connection(service.scope).await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.unmask_unit_files(vec![service.name], false).await?;
+    manager_proxy.reload().await?;
+    Ok(())
+  }
+
+  tokio::select! {
+    _ = cancel_token.cancelled() => {
+        Err(SystemdError::Cancelled)
+    }
+    result = unmask(service) => {
+        result
+    }
+  }
+}
+
+/// The command [`daemon_reload`] is equivalent to, for dry runs
+pub fn daemon_reload_command(scope: UnitScope) -> &'static str {
+  match scope {
+    UnitScope::Global => "systemctl daemon-reload",
+    UnitScope::User => "systemctl --user daemon-reload",
+  }
+}
+
+/// Reload the manager's configuration so it picks up edited unit files, like `systemctl daemon-reload`.
+/// Cancellable the same way as [`start_service`]
+pub async fn daemon_reload(scope: UnitScope, cancel_token: CancellationToken) -> Result<(), SystemdError> {
+  if is_dry_run() {
+    info!("Dry run, not running: {}", daemon_reload_command(scope));
+    return Ok(());
+  }
+
+  async fn reload(scope: UnitScope) -> Result<(), SystemdError> {
+    let connection = get_connection(scope).await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.reload().await?;
+    Ok(())
+  }
+
+  tokio::select! {
+    _ = cancel_token.cancelled() => {
+        Err(SystemdError::Cancelled)
+    }
+    result = reload(scope) => {
+        result
+    }
+  }
+}
+
+/// A unit's dependency and ordering lists, from `systemctl show`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitDependencies {
+  pub requires: Vec<String>,
+  pub wants: Vec<String>,
+  pub after: Vec<String>,
+  pub before: Vec<String>,
+}
+
+impl UnitDependencies {
+  /// Each list with the property it came from, in the order they're shown
+  pub fn kinds(&self) -> [(&'static str, &[String]); 4] {
+    [("Requires", &self.requires), ("Wants", &self.wants), ("After", &self.after), ("Before", &self.before)]
+  }
+
+  /// Every unit mentioned, without duplicates
+  pub fn all_units(&self) -> Vec<String> {
+    let mut units: Vec<String> = vec![];
+    for (_, names) in self.kinds() {
+      for name in names {
+        if !units.contains(name) {
+          units.push(name.clone());
+        }
+      }
+    }
+    units
+  }
+}
+
+/// The [`UnitDependencies`] of a unit
+pub fn get_unit_dependencies(service: &UnitId) -> Result<UnitDependencies> {
+  let mut args = vec!["show", "-p", "Requires", "-p", "Wants", "-p", "After", "-p", "Before"];
+  if service.scope == UnitScope::User {
+    args.insert(0, "--user");
+  }
+  args.push(&service.name);
+
+  match cmd("systemctl", args).read() {
+    Ok(output) => Ok(parse_unit_dependencies(&output)),
+    Err(e) => anyhow::bail!("Failed to get dependencies of {}: {}", service.name, e),
+  }
+}
+
+/// Parse `Requires=a.service b.socket` style lines from `systemctl show`
+pub fn parse_unit_dependencies(output: &str) -> UnitDependencies {
+  let mut dependencies = UnitDependencies::default();
+  for line in output.lines() {
+    let Some((key, value)) = line.split_once('=') else { continue };
+    let units = value.split_whitespace().map(String::from).collect();
+    match key {
+      "Requires" => dependencies.requires = units,
+      "Wants" => dependencies.wants = units,
+      "After" => dependencies.after = units,
+      "Before" => dependencies.before = units,
+      _ => {},
+    }
+  }
+  dependencies
+}
+
+/// Services that `service` depends on or, with `reverse`, that depend on it, according to `systemctl list-dependencies`.
+/// Other unit types (mounts, sockets, targets...) are left out, restarting those as a side effect would be surprising
+pub fn get_dependencies(service: &UnitId, reverse: bool) -> Result<Vec<String>> {
+  let mut args = vec!["list-dependencies", "--plain", "--no-legend", "--no-pager"];
+  if reverse {
+    args.push("--reverse");
+  }
+  if service.scope == UnitScope::User {
+    args.push("--user");
+  }
+  args.push(&service.name);
+
+  let output = cmd("systemctl", args).read()?;
+  Ok(parse_dependencies(&output, &service.name))
+}
+
+pub fn parse_dependencies(output: &str, unit_name: &str) -> Vec<String> {
+  let mut dependencies: Vec<String> = vec![];
+  for line in output.lines() {
+    // lines may be prefixed with a status glyph, the unit name is always last
+    let Some(name) = line.split_whitespace().last() else { continue };
+    if name == unit_name || !name.ends_with(".service") || dependencies.iter().any(|d| d == name) {
+      continue;
+    }
+    dependencies.push(name.to_string());
+  }
+  dependencies
+}
+
 // useless function only added to test that cancellation works
 pub async fn sleep_test(_service: String, cancel_token: CancellationToken) -> Result<()> {
   // god these select macros are ugly, is there really no better way to select?
@@ -241,6 +1252,10 @@ pub async fn sleep_test(_service: String, cancel_token: CancellationToken) -> Re
 
 /// Proxy object for `org.freedesktop.systemd1.Manager`.
 /// Partially taken from https://github.com/lucab/zbus_systemd/blob/main/src/systemd1/generated.rs
+///
+/// Methods that change things allow interactive auth: when we're not privileged enough, polkit asks the user's
+/// authentication agent (if one is running) to prompt for a password and the call goes ahead once that succeeds.
+/// Without an agent the call fails with `InteractiveAuthorizationRequired`/`AccessDenied` as before
 #[proxy(
   interface = "org.freedesktop.systemd1.Manager",
   default_service = "org.freedesktop.systemd1",
@@ -249,19 +1264,23 @@ pub async fn sleep_test(_service: String, cancel_token: CancellationToken) -> Re
 )]
 pub trait Manager {
   /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#StartUnit()) Call interface method `StartUnit`.
-  #[dbus_proxy(name = "StartUnit")]
+  #[dbus_proxy(name = "StartUnit", allow_interactive_auth)]
   fn start_unit(&self, name: String, mode: String) -> zbus::Result<zvariant::OwnedObjectPath>;
 
   /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#StopUnit()) Call interface method `StopUnit`.
-  #[dbus_proxy(name = "StopUnit")]
+  #[dbus_proxy(name = "StopUnit", allow_interactive_auth)]
   fn stop_unit(&self, name: String, mode: String) -> zbus::Result<zvariant::OwnedObjectPath>;
 
   /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#RestartUnit()) Call interface method `RestartUnit`.
-  #[dbus_proxy(name = "RestartUnit")]
+  #[dbus_proxy(name = "RestartUnit", allow_interactive_auth)]
   fn restart_unit(&self, name: String, mode: String) -> zbus::Result<zvariant::OwnedObjectPath>;
 
+  /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#ReloadUnit()) Call interface method `ReloadUnit`.
+  #[dbus_proxy(name = "ReloadUnit", allow_interactive_auth)]
+  fn reload_unit(&self, name: String, mode: String) -> zbus::Result<zvariant::OwnedObjectPath>;
+
   /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#EnableUnitFiles()) Call interface method `EnableUnitFiles`.
-  #[dbus_proxy(name = "EnableUnitFiles")]
+  #[dbus_proxy(name = "EnableUnitFiles", allow_interactive_auth)]
   fn enable_unit_files(
     &self,
     files: Vec<String>,
@@ -270,9 +1289,26 @@ pub trait Manager {
   ) -> zbus::Result<(bool, Vec<(String, String, String)>)>;
 
   /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#DisableUnitFiles()) Call interface method `DisableUnitFiles`.
-  #[dbus_proxy(name = "DisableUnitFiles")]
+  #[dbus_proxy(name = "DisableUnitFiles", allow_interactive_auth)]
   fn disable_unit_files(&self, files: Vec<String>, runtime: bool) -> zbus::Result<Vec<(String, String, String)>>;
 
+  /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#MaskUnitFiles()) Call interface method `MaskUnitFiles`.
+  #[dbus_proxy(name = "MaskUnitFiles", allow_interactive_auth)]
+  fn mask_unit_files(
+    &self,
+    files: Vec<String>,
+    runtime: bool,
+    force: bool,
+  ) -> zbus::Result<Vec<(String, String, String)>>;
+
+  /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#UnmaskUnitFiles()) Call interface method `UnmaskUnitFiles`.
+  #[dbus_proxy(name = "UnmaskUnitFiles", allow_interactive_auth)]
+  fn unmask_unit_files(&self, files: Vec<String>, runtime: bool) -> zbus::Result<Vec<(String, String, String)>>;
+
+  /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#KillUnit()) Call interface method `KillUnit`.
+  #[dbus_proxy(name = "KillUnit", allow_interactive_auth)]
+  fn kill_unit(&self, name: String, whom: String, signal: i32) -> zbus::Result<()>;
+
   /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#ListUnits()) Call interface method `ListUnits`.
   #[dbus_proxy(name = "ListUnits")]
   fn list_units(
@@ -313,8 +1349,16 @@ pub trait Manager {
     )>,
   >;
 
+  /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#ListUnitFilesByPatterns()) Call interface method `ListUnitFilesByPatterns`.
+  #[dbus_proxy(name = "ListUnitFilesByPatterns")]
+  fn list_unit_files_by_patterns(
+    &self,
+    states: Vec<String>,
+    patterns: Vec<String>,
+  ) -> zbus::Result<Vec<(String, String)>>;
+
   /// [📖](https://www.freedesktop.org/software/systemd/man/systemd.directives.html#Reload()) Call interface method `Reload`.
-  #[dbus_proxy(name = "Reload")]
+  #[dbus_proxy(name = "Reload", allow_interactive_auth)]
   fn reload(&self) -> zbus::Result<()>;
 }
 
@@ -354,46 +1398,30 @@ trait Service {
   fn main_pid(&self) -> zbus::Result<u32>;
 }
 
-/// Returns the load state of a systemd unit
-///
-/// Returns `invalid-unit-path` if the path is invalid
+/// Returns the active state of a systemd unit, e.g. `active` or `failed`
 ///
 /// # Arguments
 ///
 /// * `connection`: zbus connection
 /// * `full_service_name`: Full name of the service name with '.service' in the end
 ///
-pub async fn get_active_state(connection: &Connection, full_service_name: &str) -> String {
-  let object_path = get_unit_path(full_service_name);
-
-  match zvariant::ObjectPath::try_from(object_path) {
-    Ok(path) => {
-      let unit_proxy = UnitProxy::new(connection, path).await.unwrap();
-      unit_proxy.active_state().await.unwrap_or("invalid-unit-path".into())
-    },
-    Err(_) => "invalid-unit-path".to_string(),
-  }
+pub async fn get_active_state(connection: &Connection, full_service_name: &str) -> Result<String> {
+  let path = zvariant::ObjectPath::try_from(get_unit_path(full_service_name))?;
+  let unit_proxy = UnitProxy::new(connection, path).await?;
+  Ok(unit_proxy.active_state().await?)
 }
 
 /// Returns the unit file state of a systemd unit. If the state is `enabled`, the unit loads on every boot
 ///
-/// Returns `invalid-unit-path` if the path is invalid
-///
 /// # Arguments
 ///
 /// * `connection`: zbus connection
 /// * `full_service_name`: Full name of the service name with '.service' in the end
 ///
-pub async fn get_unit_file_state(connection: &Connection, full_service_name: &str) -> String {
-  let object_path = get_unit_path(full_service_name);
-
-  match zvariant::ObjectPath::try_from(object_path) {
-    Ok(path) => {
-      let unit_proxy = UnitProxy::new(connection, path).await.unwrap();
-      unit_proxy.unit_file_state().await.unwrap_or("invalid-unit-path".into())
-    },
-    Err(_) => "invalid-unit-path".to_string(),
-  }
+pub async fn get_unit_file_state(connection: &Connection, full_service_name: &str) -> Result<String> {
+  let path = zvariant::ObjectPath::try_from(get_unit_path(full_service_name))?;
+  let unit_proxy = UnitProxy::new(connection, path).await?;
+  Ok(unit_proxy.unit_file_state().await?)
 }
 
 /// Returns the PID of a systemd service
@@ -404,11 +1432,8 @@ pub async fn get_unit_file_state(connection: &Connection, full_service_name: &st
 /// * `full_service_name`: Full name of the service name with '.service' in the end
 ///
 pub async fn get_main_pid(connection: &Connection, full_service_name: &str) -> Result<u32, zbus::Error> {
-  let object_path = get_unit_path(full_service_name);
-
-  let validated_object_path = zvariant::ObjectPath::try_from(object_path).unwrap();
-
-  let service_proxy = ServiceProxy::new(connection, validated_object_path).await.unwrap();
+  let path = zvariant::ObjectPath::try_from(get_unit_path(full_service_name))?;
+  let service_proxy = ServiceProxy::new(connection, path).await?;
   service_proxy.main_pid().await
 }
 
@@ -434,3 +1459,127 @@ fn encode_as_dbus_object_path(input_string: &str) -> String {
 pub fn get_unit_path(full_service_name: &str) -> String {
   format!("/org/freedesktop/systemd1/unit/{}", encode_as_dbus_object_path(full_service_name))
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_unit_properties_full() {
+    let output = "FragmentPath=/usr/lib/systemd/system/nginx.service\nInvocationID=0a1b2c3d4e5f\nExecStart={ path=/usr/sbin/nginx ; argv[]=/usr/sbin/nginx -g daemon off; ; ignore_errors=no ; start_time=[n/a] }\nDropInPaths=/etc/systemd/system/nginx.service.d/a.conf /run/systemd/system/nginx.service.d/b.conf\nEnvironment=LANG=C \"GREETING=hello world\"\nMemoryCurrent=44150784\nCPUUsageNSec=1300000000\nUnitFileState=enabled\nActiveEnterTimestamp=Tue 2024-05-14 10:22:33 UTC\n";
+    let properties = parse_unit_properties(output);
+    assert_eq!(properties.fragment_path.as_deref(), Some("/usr/lib/systemd/system/nginx.service"));
+    assert_eq!(properties.invocation_id.as_deref(), Some("0a1b2c3d4e5f"));
+    assert_eq!(
+      properties.drop_in_paths,
+      vec!["/etc/systemd/system/nginx.service.d/a.conf", "/run/systemd/system/nginx.service.d/b.conf"]
+    );
+    assert_eq!(properties.exec_start, vec!["/usr/sbin/nginx -g daemon off;"]);
+    assert_eq!(properties.memory_current, Some(44150784));
+    assert_eq!(properties.cpu_usage_nsec, Some(1300000000));
+    assert_eq!(properties.unit_file_state.as_deref(), Some("enabled"));
+    assert_eq!(properties.active_enter_timestamp, Some(1715682153));
+    assert_eq!(properties.environment, vec![("LANG".into(), "C".into()), ("GREETING".into(), "hello world".into())]);
+  }
+
+  #[test]
+  fn parse_unit_properties_tolerates_missing_fields() {
+    // an older systemd that doesn't know InvocationID, plus output cut off mid-line
+    let output = "FragmentPath=/etc/systemd/system/foo.service\nUnknownProperty=yes\nInvoca";
+    let properties = parse_unit_properties(output);
+    assert_eq!(properties.fragment_path.as_deref(), Some("/etc/systemd/system/foo.service"));
+    assert_eq!(properties.invocation_id, None);
+  }
+
+  #[test]
+  fn parse_unit_properties_empty_values_are_none() {
+    let properties = parse_unit_properties("FragmentPath=\nInvocationID=\nMainPID=0\nActiveEnterTimestamp=\n");
+    assert_eq!(properties, UnitProperties::default());
+    // never active, on newer and older versions
+    assert_eq!(parse_unit_properties("ActiveEnterTimestamp=0\n"), UnitProperties::default());
+    assert_eq!(parse_unit_properties("ActiveEnterTimestamp=n/a\n"), UnitProperties::default());
+
+    // no accounting, or not running
+    let properties = parse_unit_properties("MemoryCurrent=[not set]\nCPUUsageNSec=18446744073709551615\n");
+    assert_eq!(properties, UnitProperties::default());
+
+    assert_eq!(parse_unit_properties(""), UnitProperties::default());
+  }
+
+  #[test]
+  fn shell_quote_only_when_needed() {
+    assert_eq!(shell_quote("--output=short-iso"), "--output=short-iso");
+    assert_eq!(shell_quote("user@1000.service"), "user@1000.service");
+    assert_eq!(shell_quote(r"dev-disk-by\x2dlabel.device"), r"'dev-disk-by\x2dlabel.device'");
+    assert_eq!(shell_quote("it's here"), r"'it'\''s here'");
+    assert_eq!(shell_quote(""), "''");
+  }
+
+  #[test]
+  fn parse_unit_dependencies_lists() {
+    let output = "Requires=system.slice sysinit.target\nWants=\nAfter=network.target basic.target\nBefore=halt.target";
+    let dependencies = parse_unit_dependencies(output);
+    assert_eq!(dependencies.requires, vec!["system.slice", "sysinit.target"]);
+    assert!(dependencies.wants.is_empty());
+    assert_eq!(dependencies.after, vec!["network.target", "basic.target"]);
+    assert_eq!(dependencies.before, vec!["halt.target"]);
+
+    // properties an older systemd doesn't print, or output cut off mid-line
+    let dependencies = parse_unit_dependencies("Requires=dbus.socket\nAft");
+    assert_eq!(dependencies, UnitDependencies { requires: vec!["dbus.socket".into()], ..Default::default() });
+  }
+
+  #[test]
+  fn parse_dependencies_services_only() {
+    let output = "nginx.service\n● network-online.target\n○ php-fpm.service\n  php-fpm.service\n× redis.service\n";
+    assert_eq!(parse_dependencies(output, "nginx.service"), vec!["php-fpm.service", "redis.service"]);
+    assert!(parse_dependencies("", "nginx.service").is_empty());
+  }
+
+  #[test]
+  fn parse_boots_list() {
+    let output = "IDX BOOT ID                          FIRST ENTRY                 LAST ENTRY\n\
+                  -1 8e1d4b2c6a0f4c3e9d7b5a1f2e3d4c5b Thu 2024-01-04 09:12:01 UTC Thu 2024-01-04 17:30:44 UTC\n\
+                  0 0f9e8d7c6b5a49382716051f2e3d4c5b Fri 2024-01-05 08:00:00 UTC Fri 2024-01-05 08:05:30 UTC\n";
+    let boots = parse_boots(output);
+    assert_eq!(boots.len(), 2);
+    assert_eq!(boots[0].offset, -1);
+    assert_eq!(boots[0].id, "8e1d4b2c6a0f4c3e9d7b5a1f2e3d4c5b");
+    assert_eq!(boots[0].first_entry, "Thu 2024-01-04 09:12:01 UTC");
+    assert_eq!(boots[0].last_entry, "Thu 2024-01-04 17:30:44 UTC");
+    assert_eq!(boots[0].duration, Some(chrono::Duration::seconds(8 * 3600 + 18 * 60 + 43)));
+    assert_eq!(boots[1].offset, 0);
+    assert_eq!(boots[1].duration, Some(chrono::Duration::seconds(330)));
+  }
+
+  #[test]
+  fn parse_boots_tolerates_missing_fields() {
+    // a line cut off after the first timestamp, and one without any
+    let output = "-2 1a2b3c Wed 2024-01-03 10:00:00 UTC\n-1 4d5e6f\n";
+    assert!(parse_boots(output).is_empty());
+
+    // timestamps that can't be parsed still list the boot, just without a duration
+    let boots = parse_boots(" 0 7a8b9c n/a n/a n/a n/a n/a n/a n/a n/a\n");
+    assert_eq!(boots.len(), 1);
+    assert_eq!(boots[0].first_entry, "n/a n/a n/a n/a");
+    assert_eq!(boots[0].duration, None);
+
+    assert!(parse_boots("").is_empty());
+  }
+
+  #[test]
+  fn parse_state_change_timestamps_blocks() {
+    let output = "Id=nginx.service\nStateChangeTimestamp=Tue 2024-05-14 10:22:33 UTC\n\n\
+                  Id=never-started.service\nStateChangeTimestamp=\n\n\
+                  Id=old-systemd.service\nStateChangeTimestamp=n/a\n\n\
+                  Id=zero.service\nStateChangeTimestamp=0\n\n\
+                  Id=no-timestamp.service\n\n\
+                  StateChangeTimestamp=Tue 2024-05-14 10:20:00 UTC\n\n\
+                  Id=redis.service\nStateChangeTimestamp=Tue 2024-05-14 09:46:40 UTC";
+    assert_eq!(
+      parse_state_change_timestamps(output),
+      vec![("nginx.service".into(), 1715682153), ("redis.service".into(), 1715680000)]
+    );
+    assert!(parse_state_change_timestamps("").is_empty());
+  }
+}
@@ -0,0 +1,74 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+/// The colors used for each role in the UI, set in the `[theme]` section of the config.
+/// Colors are names like "light-green" or "dark-gray", "#rrggbb" hex codes or 0-255 palette indexes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+  /// Borders of the focused pane and of popups
+  #[serde(deserialize_with = "color")]
+  pub border_focused: Color,
+  /// Background of the selected row in lists
+  #[serde(deserialize_with = "color")]
+  pub highlight: Color,
+  /// Regular text, e.g. unit names that don't have a more specific color
+  #[serde(deserialize_with = "color")]
+  pub text: Color,
+  /// Secondary text like separators, hints and tags
+  #[serde(deserialize_with = "color")]
+  pub dim: Color,
+  /// Less prominent text that should still be easy to read, like counts of zero
+  #[serde(deserialize_with = "color")]
+  pub muted: Color,
+  /// Keys mentioned in titles and hints
+  #[serde(deserialize_with = "color")]
+  pub key_hint: Color,
+  /// Section headers and other accents
+  #[serde(deserialize_with = "color")]
+  pub heading: Color,
+  #[serde(deserialize_with = "color")]
+  pub log_timestamp: Color,
+  #[serde(deserialize_with = "color")]
+  pub state_active: Color,
+  #[serde(deserialize_with = "color")]
+  pub state_inactive: Color,
+  /// Failed units, errors and removed lines in diffs
+  #[serde(deserialize_with = "color")]
+  pub state_failed: Color,
+  /// Units in transition or with a missing unit file
+  #[serde(deserialize_with = "color")]
+  pub state_warning: Color,
+  /// Background of search matches, bookmarks and the dry run badge
+  #[serde(deserialize_with = "color")]
+  pub marker: Color,
+  /// Text on top of `marker`
+  #[serde(deserialize_with = "color")]
+  pub marker_text: Color,
+}
+
+impl Default for Theme {
+  fn default() -> Self {
+    Self {
+      border_focused: Color::LightGreen,
+      highlight: Color::DarkGray,
+      text: Color::White,
+      dim: Color::DarkGray,
+      muted: Color::Gray,
+      key_hint: Color::Gray,
+      heading: Color::Cyan,
+      log_timestamp: Color::DarkGray,
+      state_active: Color::Green,
+      state_inactive: Color::Gray,
+      state_failed: Color::Red,
+      state_warning: Color::Yellow,
+      marker: Color::Yellow,
+      marker_text: Color::Black,
+    }
+  }
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+  let value = String::deserialize(deserializer)?;
+  value.parse().map_err(|_| serde::de::Error::custom(format!("unknown color `{value}`")))
+}